@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::AircraftInfo;
+
+use AircraftCategory::*;
+use WakeCategory::{Heavy as H, Light as L, Medium as M, Super as J};
+
+/// Broad aircraft category, used for symbology, tags and filters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AircraftCategory {
+    /// Wide-body and other heavy jets
+    Heavy,
+    /// Narrow-body and regional jets
+    Jet,
+    Turboprop,
+    /// Light piston and general aviation aircraft
+    Light,
+    Helicopter,
+    Military,
+    #[default]
+    Unknown,
+}
+
+impl AircraftCategory {
+    /// Short code shown in data tags
+    pub fn code(self) -> &'static str {
+        match self {
+            AircraftCategory::Heavy => "HVY",
+            AircraftCategory::Jet => "JET",
+            AircraftCategory::Turboprop => "TPR",
+            AircraftCategory::Light => "GA",
+            AircraftCategory::Helicopter => "HEL",
+            AircraftCategory::Military => "MIL",
+            AircraftCategory::Unknown => "",
+        }
+    }
+}
+
+/// ICAO wake turbulence category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Deserialize, Serialize)]
+pub enum WakeCategory {
+    #[serde(rename = "L")]
+    Light,
+    #[default]
+    #[serde(rename = "M")]
+    Medium,
+    #[serde(rename = "H")]
+    Heavy,
+    #[serde(rename = "J")]
+    Super,
+}
+
+impl WakeCategory {
+    /// Single-letter ICAO code
+    pub fn code(self) -> &'static str {
+        match self {
+            WakeCategory::Light => "L",
+            WakeCategory::Medium => "M",
+            WakeCategory::Heavy => "H",
+            WakeCategory::Super => "J",
+        }
+    }
+}
+
+/// Classification of an aircraft type
+#[derive(Debug, Clone, PartialEq)]
+pub struct AircraftTypeInfo {
+    pub category: AircraftCategory,
+    pub wake: WakeCategory,
+    /// ICAO type designator (empty if unknown)
+    pub icao: String,
+    /// Typical cruise speed in knots
+    pub cruise_speed: f64,
+    /// Typical final approach speed in knots
+    pub approach_speed: f64,
+}
+
+impl Default for AircraftTypeInfo {
+    fn default() -> Self {
+        Self {
+            category: AircraftCategory::Unknown,
+            wake: WakeCategory::Medium,
+            icao: String::new(),
+            cruise_speed: 250.0,
+            approach_speed: 140.0,
+        }
+    }
+}
+
+/// User-defined aircraft type entry, checked before the built-in table
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AircraftTypeEntry {
+    /// Whole words in the PTFS aircraft type name (case-insensitive)
+    pub pattern: String,
+
+    /// ICAO type designator
+    #[serde(default)]
+    pub icao: String,
+
+    pub category: AircraftCategory,
+
+    pub wake: WakeCategory,
+
+    /// Typical cruise speed in knots
+    #[serde(default)]
+    pub cruise_speed: Option<f64>,
+
+    /// Typical final approach speed in knots
+    #[serde(default)]
+    pub approach_speed: Option<f64>,
+}
+
+/// Built-in PTFS types: (name pattern, ICAO, category, wake, cruise kt, approach kt)
+/// Patterns match whole words and the first match wins, so more specific
+/// patterns must come before generic ones that may appear in the same name.
+const BUILTIN_TYPES: &[(&str, &str, AircraftCategory, WakeCategory, f64, f64)] = &[
+    // Heavies
+    ("a380", "A388", Heavy, J, 490.0, 145.0),
+    ("an-225", "A225", Heavy, J, 430.0, 150.0),
+    ("747", "B744", Heavy, H, 490.0, 155.0),
+    ("777", "B77W", Heavy, H, 490.0, 150.0),
+    ("787", "B789", Heavy, H, 488.0, 145.0),
+    ("767", "B763", Heavy, H, 470.0, 140.0),
+    ("a350", "A359", Heavy, H, 488.0, 140.0),
+    ("a340", "A346", Heavy, H, 475.0, 145.0),
+    ("a330", "A333", Heavy, H, 470.0, 140.0),
+    ("beluga", "A3ST", Heavy, H, 420.0, 135.0),
+    ("a300", "A306", Heavy, H, 460.0, 135.0),
+    ("md-11", "MD11", Heavy, H, 480.0, 155.0),
+    ("dc-10", "DC10", Heavy, H, 480.0, 150.0),
+    ("concorde", "CONC", Heavy, H, 1170.0, 160.0),
+    ("c-5", "C5M", Military, H, 450.0, 140.0),
+    ("c-17", "C17", Military, H, 450.0, 130.0),
+    ("kc-10", "KC10", Military, H, 480.0, 150.0),
+    ("a400m", "A400", Military, H, 420.0, 125.0),
+    // Jets
+    ("757", "B752", Jet, M, 460.0, 135.0),
+    ("737", "B738", Jet, M, 450.0, 145.0),
+    ("727", "B722", Jet, M, 460.0, 140.0),
+    ("717", "B712", Jet, M, 440.0, 135.0),
+    ("707", "B703", Jet, H, 460.0, 145.0),
+    ("a321", "A321", Jet, M, 450.0, 140.0),
+    ("a320", "A320", Jet, M, 450.0, 135.0),
+    ("a319", "A319", Jet, M, 450.0, 130.0),
+    ("a318", "A318", Jet, M, 450.0, 125.0),
+    ("a220", "BCS3", Jet, M, 450.0, 130.0),
+    ("md-90", "MD90", Jet, M, 440.0, 135.0),
+    ("md-80", "MD82", Jet, M, 440.0, 135.0),
+    ("md-82", "MD82", Jet, M, 440.0, 135.0),
+    ("md-83", "MD83", Jet, M, 440.0, 135.0),
+    ("md-88", "MD88", Jet, M, 440.0, 135.0),
+    ("e190", "E190", Jet, M, 440.0, 125.0),
+    ("e175", "E175", Jet, M, 440.0, 125.0),
+    ("embraer", "E190", Jet, M, 440.0, 125.0),
+    ("crj", "CRJ7", Jet, M, 440.0, 135.0),
+    ("bae 146", "B463", Jet, M, 400.0, 125.0),
+    ("learjet", "LJ45", Jet, M, 460.0, 130.0),
+    ("citation", "C56X", Jet, M, 430.0, 115.0),
+    ("gulfstream", "GLF6", Jet, M, 480.0, 130.0),
+    ("hondajet", "HDJT", Jet, L, 420.0, 110.0),
+    ("cirrus vision", "SF50", Jet, L, 300.0, 90.0),
+    // Turboprops
+    ("atr", "AT76", Turboprop, M, 275.0, 115.0),
+    ("dash 8", "DH8D", Turboprop, M, 300.0, 120.0),
+    ("q400", "DH8D", Turboprop, M, 300.0, 120.0),
+    ("twin otter", "DHC6", Turboprop, L, 170.0, 80.0),
+    ("caravan", "C208", Turboprop, L, 180.0, 80.0),
+    ("king air", "BE20", Turboprop, L, 280.0, 110.0),
+    // Helicopters
+    ("chinook", "H47", Helicopter, M, 150.0, 60.0),
+    ("black hawk", "H60", Helicopter, L, 150.0, 60.0),
+    ("apache", "H64", Helicopter, L, 150.0, 60.0),
+    ("bell", "B412", Helicopter, L, 120.0, 60.0),
+    ("sikorsky", "S92", Helicopter, M, 145.0, 60.0),
+    ("airbus h", "EC35", Helicopter, L, 130.0, 60.0),
+    ("helicopter", "", Helicopter, L, 120.0, 60.0),
+    // Military fixed wing
+    ("c-130", "C130", Military, M, 300.0, 120.0),
+    ("hercules", "C130", Military, M, 300.0, 120.0),
+    ("f-14", "F14", Military, M, 500.0, 140.0),
+    ("f-15", "F15", Military, M, 500.0, 150.0),
+    ("f-16", "F16", Military, M, 500.0, 140.0),
+    ("f/a-18", "F18H", Military, M, 500.0, 140.0),
+    ("f-22", "F22", Military, M, 520.0, 140.0),
+    ("f-35", "F35", Military, M, 500.0, 140.0),
+    ("eurofighter", "EUFI", Military, M, 500.0, 140.0),
+    ("typhoon", "EUFI", Military, M, 500.0, 140.0),
+    ("hawk", "HAWK", Military, L, 420.0, 120.0),
+    ("harrier", "HAR", Military, M, 450.0, 120.0),
+    ("p-8", "P8", Military, M, 450.0, 145.0),
+    ("e-3", "E3TF", Military, H, 420.0, 140.0),
+    // Light aircraft
+    ("cessna 172", "C172", Light, L, 120.0, 65.0),
+    ("cessna 182", "C182", Light, L, 140.0, 70.0),
+    ("cessna", "C172", Light, L, 120.0, 65.0),
+    ("cub", "J3", Light, L, 75.0, 45.0),
+    ("piper", "P28A", Light, L, 120.0, 65.0),
+    ("diamond", "DA40", Light, L, 140.0, 70.0),
+    ("cirrus", "SR22", Light, L, 180.0, 80.0),
+    ("extra 300", "E300", Light, L, 180.0, 80.0),
+];
+
+/// Whether `pattern` is in `name` as whole words, e.g. "hawk" in "bae hawk" but not in "skyhawk"
+/// Letters and digits count as separate words, so "atr" is in "atr72".
+fn contains_word(name: &str, pattern: &str) -> bool {
+    let joined = |a: Option<char>, b: Option<char>| match (a, b) {
+        (Some(a), Some(b)) => a.is_alphanumeric() && b.is_alphanumeric() && a.is_alphabetic() == b.is_alphabetic(),
+        _ => false,
+    };
+
+    name.match_indices(pattern).any(|(start, _)| {
+        let before = name[..start].chars().next_back();
+        let after = name[start + pattern.len()..].chars().next();
+        !joined(before, pattern.chars().next()) && !joined(pattern.chars().next_back(), after)
+    })
+}
+
+/// Classify an aircraft, checking user entries before the built-in table
+///
+/// Unknown types without an `isOnGround` field are taken to be helicopters,
+/// as the feed omits it only for them.
+pub fn classify(overrides: &[AircraftTypeEntry], info: &AircraftInfo) -> AircraftTypeInfo {
+    let name = info.aircraft_type.to_lowercase();
+
+    if let Some(entry) = overrides.iter().find(|e| contains_word(&name, &e.pattern.to_lowercase())) {
+        let defaults = AircraftTypeInfo::default();
+        return AircraftTypeInfo {
+            category: entry.category,
+            wake: entry.wake,
+            icao: entry.icao.clone(),
+            cruise_speed: entry.cruise_speed.unwrap_or(defaults.cruise_speed),
+            approach_speed: entry.approach_speed.unwrap_or(defaults.approach_speed),
+        };
+    }
+
+    if let Some((_, icao, category, wake, cruise, approach)) =
+        BUILTIN_TYPES.iter().find(|(pattern, ..)| contains_word(&name, pattern))
+    {
+        return AircraftTypeInfo {
+            category: *category,
+            wake: *wake,
+            icao: icao.to_string(),
+            cruise_speed: *cruise,
+            approach_speed: *approach,
+        };
+    }
+
+    if info.is_on_ground.is_none() {
+        return AircraftTypeInfo {
+            category: AircraftCategory::Helicopter,
+            wake: WakeCategory::Light,
+            cruise_speed: 120.0,
+            approach_speed: 60.0,
+            ..Default::default()
+        };
+    }
+
+    AircraftTypeInfo::default()
+}
@@ -0,0 +1,70 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::types::TrackedAircraft;
+use crate::wake::{WakeAdvisory, WakeMode};
+
+/// Why an aircraft needs attention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertKind {
+    Emergency,
+    Wake,
+}
+
+impl AlertKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            AlertKind::Emergency => "EMERG",
+            AlertKind::Wake => "WAKE",
+        }
+    }
+}
+
+/// Condition on one aircraft needing a controller's attention
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub callsign: String,
+    pub message: String,
+
+    /// Acknowledged by the controller but still going on
+    pub acknowledged: bool,
+}
+
+/// Emergencies and wake infringements, most urgent first
+pub fn current_alerts(
+    aircraft: &HashMap<String, TrackedAircraft>,
+    advisories: &HashMap<String, WakeAdvisory>,
+    acknowledged: &HashSet<String>,
+    wake_mode: WakeMode
+) -> Vec<Alert> {
+    let emergencies = aircraft
+        .values()
+        .filter(|tracked| tracked.info.is_emergency_occuring)
+        .map(|tracked| Alert {
+            kind: AlertKind::Emergency,
+            callsign: tracked.callsign.clone(),
+            message: "emergency declared".to_string(),
+            acknowledged: false,
+        });
+
+    let wake = advisories
+        .iter()
+        .filter(|(_, advisory)| advisory.infringed)
+        .map(|(callsign, advisory)| Alert {
+            kind: AlertKind::Wake,
+            callsign: callsign.clone(),
+            message: format!(
+                "{} behind {}, needs {:.1}NM",
+                advisory.gap_text(wake_mode),
+                advisory.leader,
+                advisory.required_nm
+            ),
+            acknowledged: acknowledged.contains(callsign),
+        });
+
+    let mut alerts: Vec<Alert> = emergencies.chain(wake).collect();
+    alerts.sort_by(|a, b| (a.acknowledged, a.kind, &a.callsign).cmp(&(b.acknowledged, b.kind, &b.callsign)));
+    alerts
+}
@@ -0,0 +1,72 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use crate::retention::{with_last_seen, Kept};
+
+/// Controller's own entries for an aircraft
+///
+/// Kept by callsign when the aircraft drops off the feed, so they are
+/// still there when it reappears, until it has been gone too long.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Annotation {
+    /// Assigned heading in degrees
+    #[serde(default)]
+    pub heading: Option<u16>,
+
+    /// Assigned speed in knots
+    #[serde(default)]
+    pub speed: Option<u16>,
+
+    /// Cleared altitude in feet
+    #[serde(default)]
+    pub cleared_level: Option<u32>,
+
+    /// Cleared runway, e.g. "25R"
+    #[serde(default)]
+    pub runway: String,
+
+    /// Free text shown in the tag
+    #[serde(default)]
+    pub scratchpad: String,
+}
+
+impl Annotation {
+    /// Whether nothing has been entered
+    pub fn is_empty(&self) -> bool {
+        *self == Annotation::default()
+    }
+}
+
+/// Get the annotations file path
+pub fn annotations_path() -> PathBuf {
+    crate::config::data_path("annotations.json")
+}
+
+/// Load saved annotations, or none if there is no annotations file yet
+pub fn load_annotations() -> Result<HashMap<String, Kept<Annotation>>> {
+    let path = annotations_path();
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .context("Failed to read annotations.json")?;
+
+    serde_json::from_str(&contents).context("Failed to parse annotations.json")
+}
+
+/// Save annotations to file, with when their aircraft were last seen
+pub fn save_annotations(annotations: &HashMap<String, Annotation>, last_seen: &HashMap<String, i64>) -> Result<()> {
+    let sorted: BTreeMap<_, _> = with_last_seen(annotations, last_seen).into_iter().collect();
+
+    let contents = serde_json::to_string_pretty(&sorted)
+        .context("Failed to serialize annotations")?;
+
+    std::fs::write(annotations_path(), contents)
+        .context("Failed to write annotations.json")?;
+
+    Ok(())
+}
@@ -0,0 +1,516 @@
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::aircraft_db::{AircraftCategory, WakeCategory};
+use crate::alerts::Alert;
+use crate::annotations::Annotation;
+use crate::state::RadarState;
+use crate::types::{AircraftInfo, FlightPhase, FlightPlan, TrackedAircraft};
+use crate::validation::Validator;
+use crate::wake::{WakeAdvisory, STUDS_PER_NM};
+
+/// Longest request head read before giving up on a client
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+
+/// Time a client gets to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Wait before accepting again after a failed accept, e.g. when out of file handles
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Endpoints, listed by `GET /api`
+const ENDPOINTS: [&str; 7] = [
+    "/api/state",
+    "/api/aircraft",
+    "/api/aircraft/{callsign}",
+    "/api/alerts",
+    "/api/controllers",
+    "/api/atis",
+    "/api/ws",
+];
+
+/// Local server other tools can read the radar state from
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApiConfig {
+    /// Start the server; changes to this section apply on restart
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+
+    /// Address to listen on; anything other than localhost needs a token
+    #[serde(default = "default_bind")]
+    pub bind: String,
+
+    /// Token clients must send as `Authorization: Bearer TOKEN` or `?token=TOKEN`
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// Web page origins allowed to call the API from a browser, e.g. "http://localhost:3000"
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+
+    /// Seconds between updates sent to WebSocket subscribers
+    #[serde(default = "default_update_interval")]
+    pub update_interval_secs: f32,
+}
+
+fn default_false() -> bool { false }
+fn default_bind() -> String { "127.0.0.1:7878".to_string() }
+fn default_update_interval() -> f32 { 1.0 }
+
+impl ApiConfig {
+    pub fn validate(&self, v: &mut Validator) {
+        match self.bind.parse::<SocketAddr>() {
+            Ok(addr) if !addr.ip().is_loopback() && self.token.is_none() => {
+                v.error("token", format!("required to listen on {}, which is reachable from other machines", addr));
+            }
+            Ok(_) => {}
+            Err(_) => v.error("bind", format!("\"{}\" is not an address, expected e.g. \"127.0.0.1:7878\"", self.bind)),
+        }
+        if self.token.as_deref().is_some_and(str::is_empty) {
+            v.error("token", "must not be empty");
+        }
+        v.nested("allowed_origins", |v| {
+            for (i, origin) in self.allowed_origins.iter().enumerate() {
+                v.url(&i.to_string(), origin, &["http", "https"]);
+            }
+        });
+        v.range("update_interval_secs", self.update_interval_secs, 0.1..=60.0);
+    }
+
+    /// Whether a browser page from `origin` may call the API
+    fn allows_origin(&self, origin: &str) -> bool {
+        self.allowed_origins.iter().any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+    }
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_false(),
+            bind: default_bind(),
+            token: None,
+            allowed_origins: Vec::new(),
+            update_interval_secs: default_update_interval(),
+        }
+    }
+}
+
+/// Aircraft as published, with what the radar derives from the feed
+#[derive(Debug, Clone, Serialize)]
+struct AircraftView {
+    callsign: String,
+    info: AircraftInfo,
+    flight_plan: Option<FlightPlan>,
+    kinematics: Kinematics,
+
+    /// Sequence on final, when the aircraft follows another
+    wake: Option<WakeAdvisory>,
+
+    annotation: Option<Annotation>,
+    is_event: bool,
+}
+
+/// Motion and type details derived from the feed
+#[derive(Debug, Clone, Serialize)]
+struct Kinematics {
+    /// Smoothed vertical speed in feet per minute
+    vertical_speed: f64,
+
+    phase: FlightPhase,
+
+    /// Ground velocity in studs per second, -y being north
+    velocity: (f64, f64),
+
+    category: AircraftCategory,
+    wake_category: WakeCategory,
+
+    /// ICAO type designator, empty if unknown
+    icao: String,
+}
+
+impl AircraftView {
+    fn new(tracked: TrackedAircraft, wake: Option<WakeAdvisory>, annotation: Option<Annotation>) -> Self {
+        let studs_per_sec = tracked.info.ground_speed * STUDS_PER_NM / 3600.0;
+        let heading_rad = (tracked.info.heading - 90.0).to_radians();
+
+        let kinematics = Kinematics {
+            vertical_speed: tracked.vertical_speed,
+            phase: tracked.phase(),
+            velocity: (studs_per_sec * heading_rad.cos(), studs_per_sec * heading_rad.sin()),
+            category: tracked.type_info.category,
+            wake_category: tracked.type_info.wake,
+            icao: tracked.type_info.icao.clone(),
+        };
+
+        Self {
+            callsign: tracked.callsign,
+            info: tracked.info,
+            flight_plan: tracked.flight_plan,
+            kinematics,
+            wake,
+            annotation,
+            is_event: tracked.is_event,
+        }
+    }
+}
+
+/// Everything published, by section
+///
+/// Aircraft that dropped off the feed are left out, so subscribers see them removed.
+fn snapshot(state: &RadarState) -> Map<String, Value> {
+    let status = state.get_connection_status();
+    let latest = status.last_data_received.unwrap_or_default();
+    let mut advisories = state.get_wake_advisories();
+    let mut annotations = state.get_annotations();
+    let aircraft: BTreeMap<String, AircraftView> = state
+        .get_aircraft()
+        .into_iter()
+        .filter(|(_, tracked)| !tracked.is_off_feed(latest))
+        .map(|(callsign, tracked)| {
+            let view = AircraftView::new(tracked, advisories.remove(&callsign), annotations.remove(&callsign));
+            (callsign, view)
+        })
+        .collect();
+
+    let alerts: Vec<Alert> = state.get_alerts();
+    let atis: BTreeMap<_, _> = state.get_all_atis().into_iter().collect();
+
+    let mut sections = Map::new();
+    sections.insert("status".to_string(), json!({
+        "connected": status.websocket_connected,
+        "last_data_received": status.last_data_received,
+    }));
+    sections.insert("aircraft".to_string(), json!(aircraft));
+    sections.insert("alerts".to_string(), json!(alerts));
+    sections.insert("controllers".to_string(), json!(state.get_controllers()));
+    sections.insert("atis".to_string(), json!(atis));
+    sections
+}
+
+/// Changes from one snapshot to the next, `None` if there are none
+///
+/// Aircraft are sent individually as `updated` and `removed`; other sections
+/// are sent whole when anything in them changed.
+fn delta(previous: &Map<String, Value>, current: &Map<String, Value>) -> Option<Value> {
+    let mut changes = Map::new();
+
+    for (section, value) in current {
+        let before = previous.get(section);
+        if before == Some(value) {
+            continue;
+        }
+
+        if section == "aircraft" && let (Some(Value::Object(before)), Value::Object(now)) = (before, value) {
+            let updated: Map<String, Value> = now
+                .iter()
+                .filter(|(callsign, view)| before.get(*callsign) != Some(*view))
+                .map(|(callsign, view)| (callsign.clone(), view.clone()))
+                .collect();
+            let removed: Vec<&String> = before.keys().filter(|callsign| !now.contains_key(*callsign)).collect();
+            changes.insert(section.clone(), json!({ "updated": updated, "removed": removed }));
+        } else {
+            changes.insert(section.clone(), value.clone());
+        }
+    }
+
+    (!changes.is_empty()).then(|| {
+        changes.insert("type".to_string(), json!("delta"));
+        Value::Object(changes)
+    })
+}
+
+/// HTTP request head
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+
+    /// Header names lowercased
+    headers: HashMap<String, String>,
+}
+
+impl Request {
+    /// Read a request head, leaving any body unread
+    async fn read(stream: &mut TcpStream) -> Result<Self> {
+        let mut head = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            anyhow::ensure!(head.len() < MAX_REQUEST_BYTES, "request too large");
+            let n = stream.read(&mut buf).await?;
+            anyhow::ensure!(n > 0, "connection closed");
+            head.extend_from_slice(&buf[..n]);
+        }
+
+        let head = String::from_utf8_lossy(&head);
+        let mut lines = head.lines();
+        let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+        let method = request_line.next().unwrap_or_default().to_string();
+        let target = request_line.next().unwrap_or_default();
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+            .collect();
+        let headers = lines
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+
+        Ok(Self { method, path: path.trim_end_matches('/').to_string(), query, headers })
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    /// Whether the request carries the token, if one is required
+    fn authorized(&self, token: Option<&str>) -> bool {
+        let Some(token) = token else {
+            return true;
+        };
+        let matches = |given: Option<&str>| given.is_some_and(|given| constant_time_eq(given, token));
+        matches(self.header("authorization").and_then(|value| value.strip_prefix("Bearer "))) ||
+            matches(self.query.get("token").map(String::as_str))
+    }
+
+    /// Whether a web page may have sent the request, by its `Origin` and `Host`
+    ///
+    /// Without a token anything on the machine can read the API, so a page
+    /// must be local or allowed, and `Host` must name this machine to stop
+    /// other sites reaching the server through DNS rebinding.
+    fn page_allowed(&self, config: &ApiConfig) -> bool {
+        let origin_allowed = match self.header("origin") {
+            Some(origin) => config.allows_origin(origin) || config.token.is_none() && is_local_origin(origin),
+            None => true,
+        };
+        let host_allowed = config.token.is_some() || self.header("host").is_none_or(is_local_host);
+        origin_allowed && host_allowed
+    }
+}
+
+/// Compare without stopping at the first difference, so timing doesn't give the token away
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Whether a `Host` header value such as "localhost:7878" names this machine
+fn is_local_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split_once(']').map_or(bracketed, |(address, _)| address),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost") ||
+        name.to_ascii_lowercase().ends_with(".localhost") ||
+        name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Whether an `Origin` header value such as "http://localhost:3000" is a page on this machine
+fn is_local_origin(origin: &str) -> bool {
+    origin
+        .split_once("://")
+        .is_some_and(|(scheme, host)| matches!(scheme, "http" | "https") && is_local_host(host))
+}
+
+/// Decode `%XX` escapes and, as in query strings, `+` for space
+fn percent_decode(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (byte, escaped) {
+            (b'%', Some(decoded)) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+                continue;
+            }
+            (b'+', _) => bytes.push(b' '),
+            _ => bytes.push(byte),
+        }
+        rest = tail;
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Local HTTP and WebSocket server publishing `RadarState`
+pub struct ApiServer {
+    state: Arc<RadarState>,
+}
+
+impl ApiServer {
+    pub fn new(state: Arc<RadarState>) -> Self {
+        Self { state }
+    }
+
+    /// Listen on the configured address until the runtime stops
+    pub async fn run(self) -> Result<()> {
+        let bind = self.state.get_config().api.bind;
+        let listener = TcpListener::bind(&bind)
+            .await
+            .with_context(|| format!("Failed to listen on {}", bind))?;
+        tracing::info!("API listening on http://{}/api", bind);
+
+        let state = self.state;
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // Usually passing, e.g. too many open files, so keep serving
+                    tracing::warn!("API failed to accept a connection: {}", e);
+                    tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            let state = state.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(state, stream).await {
+                    tracing::debug!("API connection from {} ended: {:#}", peer, e);
+                }
+            });
+        }
+    }
+}
+
+async fn handle_connection(state: Arc<RadarState>, mut stream: TcpStream) -> Result<()> {
+    let request = tokio::time::timeout(REQUEST_TIMEOUT, Request::read(&mut stream))
+        .await
+        .context("request timed out")??;
+
+    // Read the settings per request so changes in config.toml apply at once
+    let config = state.get_config().api;
+    let cors = request.header("origin").filter(|origin| config.allows_origin(origin));
+    let response = Response { stream, cors };
+
+    if !request.page_allowed(&config) {
+        return response.respond("403 Forbidden", &json!({ "error": "origin or host not allowed" })).await;
+    }
+    // Browsers ask before sending the token in a header; the question itself carries no token
+    if request.method == "OPTIONS" && response.cors.is_some() {
+        return response.preflight().await;
+    }
+    if !request.authorized(config.token.as_deref()) {
+        return response.respond("401 Unauthorized", &json!({ "error": "missing or wrong token" })).await;
+    }
+    if request.method != "GET" {
+        return response.respond("405 Method Not Allowed", &json!({ "error": "only GET is supported" })).await;
+    }
+
+    let path = request.path.as_str();
+    if path == "/api/ws" {
+        return subscribe(state, response, &request).await;
+    }
+
+    let mut sections = snapshot(&state);
+    let body = match path.strip_prefix("/api") {
+        Some("") => Some(json!({ "endpoints": ENDPOINTS })),
+        Some("/state") => Some(Value::Object(sections)),
+        Some(section @ ("/aircraft" | "/alerts" | "/controllers" | "/atis")) => sections.remove(&section[1..]),
+        Some(rest) => rest
+            .strip_prefix("/aircraft/")
+            .map(percent_decode)
+            .and_then(|callsign| sections.get_mut("aircraft")?.get_mut(&callsign))
+            .map(Value::take),
+        None => None,
+    };
+
+    match body {
+        Some(body) => response.respond("200 OK", &body).await,
+        None => response.respond("404 Not Found", &json!({ "error": "not found", "endpoints": ENDPOINTS })).await,
+    }
+}
+
+/// Connection to answer, with the browser origin allowed to read the answer if any
+struct Response<'a> {
+    stream: TcpStream,
+    cors: Option<&'a str>,
+}
+
+impl Response<'_> {
+    /// Answer a CORS preflight, allowing GET with a token header, and close the connection
+    async fn preflight(mut self) -> Result<()> {
+        let head = format!(
+            "HTTP/1.1 204 No Content\r\nAccess-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Methods: GET\r\nAccess-Control-Allow-Headers: Authorization\r\nAccess-Control-Max-Age: 600\r\nVary: Origin\r\nConnection: close\r\n\r\n",
+            self.cors.unwrap_or_default()
+        );
+        self.stream.write_all(head.as_bytes()).await?;
+        self.stream.shutdown().await?;
+        Ok(())
+    }
+
+    /// Write a JSON response and close the connection
+    async fn respond(mut self, status: &str, body: &Value) -> Result<()> {
+        let body = serde_json::to_string(body)?;
+        let cors = self.cors
+            .map(|origin| format!("Access-Control-Allow-Origin: {}\r\nVary: Origin\r\n", origin))
+            .unwrap_or_default();
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+            status,
+            body.len(),
+            cors
+        );
+        self.stream.write_all(head.as_bytes()).await?;
+        self.stream.write_all(body.as_bytes()).await?;
+        self.stream.shutdown().await?;
+        Ok(())
+    }
+}
+
+/// Upgrade to a WebSocket that gets a snapshot, then deltas as the state changes
+async fn subscribe(state: Arc<RadarState>, response: Response<'_>, request: &Request) -> Result<()> {
+    let upgrade = request.header("upgrade").is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+    let Some(key) = request.header("sec-websocket-key").filter(|_| upgrade) else {
+        return response.respond("400 Bad Request", &json!({ "error": "expected a WebSocket upgrade" })).await;
+    };
+    let mut stream = response.stream;
+
+    let head = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())
+    );
+    stream.write_all(head.as_bytes()).await?;
+    let mut socket = WebSocketStream::from_raw_socket(stream, Role::Server, None).await;
+
+    let mut sections = snapshot(&state);
+    let mut message = sections.clone();
+    message.insert("type".to_string(), json!("snapshot"));
+    socket.send(Message::Text(Value::Object(message).to_string())).await?;
+
+    let interval = Duration::from_secs_f32(state.get_config().api.update_interval_secs.max(0.1));
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let current = snapshot(&state);
+                if let Some(changes) = delta(&sections, &current) {
+                    socket.send(Message::Text(changes.to_string())).await?;
+                }
+                sections = current;
+            }
+            incoming = socket.next() => match incoming {
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// Usage shown by `--help`
+pub const USAGE: &str = "\
+Usage: feritscope [OPTIONS]
+
+Options:
+  --config <file>      Config file to use
+  --profile <name>     Config profile to start with, `base` for none
+  --replay <file>      Play back a recorded feed instead of connecting
+  --headless           Run without a window, showing a terminal dashboard
+                       (enter `a` to acknowledge wake alerts, `q` to quit)
+  --log-level <level>  Log filter, e.g. `info` or `feritscope=debug,warn`
+  -h, --help           Show this help
+  -V, --version        Show the version
+
+Options given here win over the environment variables FERITSCOPE_CONFIG,
+FERITSCOPE_PROFILE and FERITSCOPE_LOG (or RUST_LOG), which win over config.toml.";
+
+/// Default log filter when neither `--log-level` nor the environment sets one
+const DEFAULT_LOG_LEVEL: &str = "feritscope=debug,info";
+
+/// Startup options, from the command line and then the environment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Args {
+    /// Config file to use instead of the platform config directory
+    pub config: Option<PathBuf>,
+
+    /// Profile to start with, overriding `active_profile` in the file
+    /// `Some(None)` starts on the base config.
+    pub profile: Option<Option<String>>,
+
+    /// Recorded feed to play back, one feed message per line
+    pub replay: Option<PathBuf>,
+
+    pub headless: bool,
+
+    /// Log filter in `tracing_subscriber::EnvFilter` syntax
+    pub log_level: String,
+}
+
+/// What to do after reading the command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Invocation {
+    Run(Args),
+    Help,
+    Version,
+}
+
+/// Read options from the process command line and environment
+pub fn from_env() -> Result<Invocation> {
+    let invocation = parse(std::env::args().skip(1))?;
+    Ok(match invocation {
+        Invocation::Run(args) => Invocation::Run(args.with_env(|name| std::env::var(name).ok())),
+        other => other,
+    })
+}
+
+/// Parse command line arguments, excluding the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Invocation> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // Accept both `--flag value` and `--flag=value`
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg, None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .with_context(|| format!("{} needs a value, see --help", flag))
+        };
+
+        match flag.as_str() {
+            "--config" => parsed.config = Some(PathBuf::from(value()?)),
+            "--profile" => parsed.profile = Some(profile(value()?)),
+            "--replay" => parsed.replay = Some(PathBuf::from(value()?)),
+            "--headless" => parsed.headless = true,
+            "--log-level" => parsed.log_level = value()?,
+            "-h" | "--help" => return Ok(Invocation::Help),
+            "-V" | "--version" => return Ok(Invocation::Version),
+            _ => anyhow::bail!("unknown option `{}`, see --help", flag),
+        }
+    }
+
+    Ok(Invocation::Run(parsed))
+}
+
+/// Profile named on the command line or in the environment, `base` meaning none
+fn profile(name: String) -> Option<String> {
+    (name != "base").then_some(name)
+}
+
+impl Args {
+    /// Fill in options the command line left out from environment variables
+    pub fn with_env(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+
+        if self.config.is_none() {
+            self.config = var("FERITSCOPE_CONFIG").map(PathBuf::from);
+        }
+        if self.profile.is_none() {
+            self.profile = var("FERITSCOPE_PROFILE").map(profile);
+        }
+        if self.log_level.is_empty() {
+            self.log_level = var("FERITSCOPE_LOG")
+                .or_else(|| var("RUST_LOG"))
+                .unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string());
+        }
+        self
+    }
+}
@@ -0,0 +1,285 @@
+use thiserror::Error;
+
+/// Error shown inline under the command line
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum CommandError {
+    #[error("commands start with `.`, try .help")]
+    NotACommand,
+
+    #[error("unknown command `.{0}`, try .help")]
+    Unknown(String),
+
+    #[error("usage: {0}")]
+    Usage(&'static str),
+
+    #[error("`{value}` is not a valid {what}")]
+    Invalid { value: String, what: &'static str },
+}
+
+/// A parsed command line entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Select an aircraft and centre on it
+    Find(String),
+    /// Centre on an airport or aircraft
+    Center(String),
+    /// Set or clear a cleared level (feet)
+    ClearedLevel { callsign: String, level: Option<u32> },
+    /// Set or clear an assigned heading
+    Heading { callsign: String, heading: Option<u16> },
+    /// Set or clear an assigned speed
+    Speed { callsign: String, speed: Option<u16> },
+    /// Set or clear a cleared runway
+    Runway { callsign: String, runway: String },
+    /// Set or clear the scratchpad
+    Scratchpad { callsign: String, text: String },
+    /// Start or stop the range and bearing ruler
+    Measure,
+    Filter(FilterCommand),
+    View(ViewCommand),
+    /// Set the zoom in studs per pixel
+    Zoom(f64),
+    /// Switch to a config profile, or back to the base config with `None`
+    Profile(Option<String>),
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterCommand {
+    /// Only show aircraft between two altitudes (feet)
+    Altitude { floor: f64, ceiling: f64 },
+    /// Turn the altitude filter off
+    AltitudeOff,
+    /// Turn all filters off
+    Off,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewCommand {
+    Save(String),
+    Recall(String),
+    Delete(String),
+}
+
+/// What a command's first argument refers to, for tab completion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    None,
+    Callsign,
+    /// Airport or callsign
+    Place,
+    /// Config profile name
+    Profile,
+}
+
+/// Command name, usage and first argument kind, in help order
+pub const COMMANDS: &[(&str, &str, ArgKind)] = &[
+    ("find", ".find <callsign>", ArgKind::Callsign),
+    ("center", ".center <airport|callsign>", ArgKind::Place),
+    ("cfl", ".cfl <callsign> [level]", ArgKind::Callsign),
+    ("hdg", ".hdg <callsign> [heading]", ArgKind::Callsign),
+    ("spd", ".spd <callsign> [speed]", ArgKind::Callsign),
+    ("rwy", ".rwy <callsign> [runway]", ArgKind::Callsign),
+    ("scratch", ".scratch <callsign> [text]", ArgKind::Callsign),
+    ("measure", ".measure", ArgKind::None),
+    ("filter", ".filter alt <floor> <ceiling> | .filter alt off | .filter off", ArgKind::None),
+    ("view", ".view <name> | .view save <name> | .view delete <name>", ArgKind::None),
+    ("zoom", ".zoom <studs per pixel>", ArgKind::None),
+    ("profile", ".profile <name|base>", ArgKind::Profile),
+    ("help", ".help", ArgKind::None),
+];
+
+fn usage(name: &str) -> &'static str {
+    COMMANDS
+        .iter()
+        .find(|(n, ..)| *n == name)
+        .map(|(_, usage, _)| *usage)
+        .unwrap_or("")
+}
+
+fn number<T: std::str::FromStr>(value: &str, what: &'static str) -> Result<T, CommandError> {
+    value.parse().map_err(|_| CommandError::Invalid { value: value.to_string(), what })
+}
+
+/// Highest level that can be cleared, in hundreds of feet
+const MAX_LEVEL: u32 = 600;
+
+/// Level in feet from hundreds of feet, with or without an `F`/`FL` prefix, e.g. 240 or FL240
+pub fn level(value: &str) -> Result<u32, CommandError> {
+    let invalid = || CommandError::Invalid { value: value.to_string(), what: "level" };
+    let upper = value.to_ascii_uppercase();
+    let digits = upper
+        .strip_prefix("FL")
+        .or_else(|| upper.strip_prefix('F'))
+        .unwrap_or(&upper);
+
+    digits
+        .parse::<u32>()
+        .ok()
+        .filter(|hundreds| *hundreds <= MAX_LEVEL)
+        .and_then(|hundreds| hundreds.checked_mul(100))
+        .ok_or_else(invalid)
+}
+
+pub fn heading(value: &str) -> Result<u16, CommandError> {
+    match number::<u16>(value, "heading")? {
+        heading @ 1..=360 => Ok(heading),
+        _ => Err(CommandError::Invalid { value: value.to_string(), what: "heading" }),
+    }
+}
+
+/// Parse a command line entry such as `.cfl BAW123 240`
+pub fn parse(input: &str) -> Result<Command, CommandError> {
+    let input = input.trim();
+    let Some(input) = input.strip_prefix('.') else {
+        return Err(CommandError::NotACommand);
+    };
+
+    let mut words = input.split_whitespace();
+    let name = words.next().unwrap_or_default().to_lowercase();
+    let args: Vec<&str> = words.collect();
+    let usage_error = || CommandError::Usage(usage(&name));
+
+    // Annotation commands share the same `<callsign> [value]` shape
+    let callsign = || args.first().map(|c| c.to_uppercase()).ok_or_else(usage_error);
+    let rest = || args.get(1..).unwrap_or_default().join(" ");
+
+    match name.as_str() {
+        "find" if args.len() == 1 => Ok(Command::Find(args[0].to_uppercase())),
+        "center" if args.len() == 1 => Ok(Command::Center(args[0].to_uppercase())),
+        "cfl" if args.len() <= 2 => Ok(Command::ClearedLevel {
+            callsign: callsign()?,
+            level: args.get(1).map(|v| level(v)).transpose()?,
+        }),
+        "hdg" if args.len() <= 2 => Ok(Command::Heading {
+            callsign: callsign()?,
+            heading: args.get(1).map(|v| heading(v)).transpose()?,
+        }),
+        "spd" if args.len() <= 2 => Ok(Command::Speed {
+            callsign: callsign()?,
+            speed: args.get(1).map(|v| number(v, "speed")).transpose()?,
+        }),
+        "rwy" if args.len() <= 2 => Ok(Command::Runway {
+            callsign: callsign()?,
+            runway: rest().to_uppercase(),
+        }),
+        "scratch" => Ok(Command::Scratchpad {
+            callsign: callsign()?,
+            text: rest(),
+        }),
+        "measure" if args.is_empty() => Ok(Command::Measure),
+        "filter" => match args.as_slice() {
+            ["off"] => Ok(Command::Filter(FilterCommand::Off)),
+            ["alt", "off"] => Ok(Command::Filter(FilterCommand::AltitudeOff)),
+            ["alt", floor, ceiling] => {
+                let floor = number(floor, "altitude")?;
+                let ceiling = number(ceiling, "altitude")?;
+                Ok(Command::Filter(FilterCommand::Altitude { floor, ceiling }))
+            }
+            _ => Err(usage_error()),
+        },
+        "view" => match args.as_slice() {
+            ["save", name] => Ok(Command::View(ViewCommand::Save(name.to_string()))),
+            ["delete", name] => Ok(Command::View(ViewCommand::Delete(name.to_string()))),
+            [name] => Ok(Command::View(ViewCommand::Recall(name.to_string()))),
+            _ => Err(usage_error()),
+        },
+        "zoom" if args.len() == 1 => match number::<f64>(args[0], "zoom")? {
+            zoom if zoom > 0.0 => Ok(Command::Zoom(zoom)),
+            _ => Err(CommandError::Invalid { value: args[0].to_string(), what: "zoom" }),
+        },
+        "profile" => match args.as_slice() {
+            ["base"] => Ok(Command::Profile(None)),
+            [name] => Ok(Command::Profile(Some(name.to_string()))),
+            _ => Err(usage_error()),
+        },
+        "help" => Ok(Command::Help),
+        _ if COMMANDS.iter().any(|(n, ..)| *n == name) => Err(usage_error()),
+        _ => Err(CommandError::Unknown(name)),
+    }
+}
+
+/// Result of tab completion
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    /// Input with the last word completed as far as possible
+    pub input: String,
+
+    /// All candidates when more than one matches
+    pub candidates: Vec<String>,
+}
+
+/// Complete the command name or its first argument
+///
+/// `callsigns`, `airports` and `profiles` are the candidates for callsign,
+/// place and profile arguments. Returns `None` when nothing matches.
+pub fn complete(
+    input: &str,
+    callsigns: &[String],
+    airports: &[String],
+    profiles: &[String]
+) -> Option<Completion> {
+    let rest = input.strip_prefix('.')?;
+    let words: Vec<&str> = rest.split(' ').collect();
+
+    let (prefix, word, candidates): (String, &str, Vec<String>) = match words.as_slice() {
+        [name] => (
+            ".".to_string(),
+            *name,
+            COMMANDS.iter().map(|(n, ..)| n.to_string()).collect(),
+        ),
+        [name, arg] => {
+            let kind = COMMANDS
+                .iter()
+                .find(|(n, ..)| n.eq_ignore_ascii_case(name))
+                .map(|(_, _, kind)| *kind)?;
+            let candidates = match kind {
+                ArgKind::None => return None,
+                ArgKind::Callsign => callsigns.to_vec(),
+                ArgKind::Place => airports.iter().chain(callsigns).cloned().collect(),
+                ArgKind::Profile => profiles.to_vec(),
+            };
+            (format!(".{} ", name), *arg, candidates)
+        }
+        _ => return None,
+    };
+
+    let word_upper = word.to_uppercase();
+    let mut matches: Vec<String> = candidates
+        .into_iter()
+        .filter(|c| c.to_uppercase().starts_with(&word_upper))
+        .collect();
+    matches.sort();
+    matches.dedup();
+
+    match matches.as_slice() {
+        [] => None,
+        [only] => Some(Completion {
+            input: format!("{}{} ", prefix, only),
+            candidates: Vec::new(),
+        }),
+        [first, ..] => {
+            // Extend to the longest prefix shared by every candidate
+            let common = matches
+                .iter()
+                .map(|candidate| {
+                    first
+                        .chars()
+                        .zip(candidate.chars())
+                        .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+                        .count()
+                })
+                .min()
+                .unwrap_or(0);
+            let completed = if common > word.chars().count() {
+                first.chars().take(common).collect()
+            } else {
+                word.to_string()
+            };
+            Some(Completion {
+                input: format!("{}{}", prefix, completed),
+                candidates: matches,
+            })
+        }
+    }
+}
@@ -1,16 +1,41 @@
 use anyhow::{Context, Result};
+use egui::Color32;
 use notify::{Config as NotifyConfig, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 use tokio::sync::mpsc;
+use toml_edit::{DocumentMut, Item, Table, Value};
 
+use crate::aircraft_db::AircraftTypeEntry;
+use crate::api::ApiConfig;
+use crate::filters::FilterConfig;
+use crate::keybindings::KeyBindings;
+use crate::migrations::{self, CONFIG_VERSION};
+use crate::radar::try_parse_color;
+use crate::rules::TargetRule;
+use crate::schema::SCHEMA_FILE;
 use crate::state::RadarState;
+use crate::template::Template;
+use crate::themes::{self, themes_dir};
+use crate::validation::{ConfigError, Validator};
+use crate::viewport::ViewportConfig;
+use crate::views::ViewsConfig;
+use crate::wake::WakeConfig;
 
 /// Main configuration structure
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RadarConfig {
+    /// Format version, used to upgrade files written by older builds
+    #[serde(default = "default_version")]
+    pub version: u32,
+    
+    /// Profile applied on top of this config, from `[profile.NAME]`
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    
     #[serde(default)]
     pub display: DisplayConfig,
     
@@ -20,6 +45,32 @@ pub struct RadarConfig {
     #[serde(default)]
     pub data_tags: DataTagConfig,
     
+    /// Target presentation rules, first match wins
+    #[serde(default)]
+    pub target_rules: Vec<TargetRule>,
+    
+    /// Extra aircraft type classifications, checked before the built-in table
+    #[serde(default)]
+    pub aircraft_types: Vec<AircraftTypeEntry>,
+    
+    #[serde(default)]
+    pub wake: WakeConfig,
+    
+    #[serde(default)]
+    pub filters: FilterConfig,
+    
+    /// Extra scope viewports (insets and windows)
+    #[serde(default)]
+    pub viewports: Vec<ViewportConfig>,
+    
+    /// Saved views, airports and the last camera position
+    #[serde(default)]
+    pub views: ViewsConfig,
+    
+    /// Key combination for each keyboard action
+    #[serde(default)]
+    pub keybindings: KeyBindings,
+    
     #[serde(default)]
     pub performance: PerformanceConfig,
     
@@ -28,6 +79,14 @@ pub struct RadarConfig {
     
     #[serde(default)]
     pub fonts: FontConfig,
+    
+    #[serde(default)]
+    pub api: ApiConfig,
+    
+    /// Named profiles overriding any part of this config
+    /// A profile may set `inherits = "other"` to build on another profile.
+    #[serde(default)]
+    pub profile: BTreeMap<String, toml::Table>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -68,9 +127,17 @@ pub struct DisplayConfig {
     #[serde(default = "default_font_size")]
     pub font_size: f32,
     
-    /// Number of history dots to show (trail length)
-    #[serde(default = "default_history_length")]
-    pub history_length: usize,
+    /// Seconds between history dots
+    #[serde(default = "default_history_interval")]
+    pub history_interval_secs: f32,
+    
+    /// How far back the history trail goes, in seconds
+    #[serde(default = "default_history_duration")]
+    pub history_duration_secs: f32,
+    
+    /// Fade history dots with age
+    #[serde(default = "default_true")]
+    pub history_fade: bool,
     
     /// History dot size in pixels
     #[serde(default = "default_history_dot_size")]
@@ -91,11 +158,39 @@ pub struct DisplayConfig {
     /// Show data tags
     #[serde(default = "default_true")]
     pub show_tags: bool,
+    
+    /// Scope rotation at startup: true heading shown at the top (0 = north up)
+    #[serde(default)]
+    pub rotation: f64,
+    
+    /// Magnetic variation in degrees, east positive
+    /// Displayed headings and bearings are magnetic (true - variation)
+    #[serde(default)]
+    pub magnetic_variation: f64,
+    
+    /// Show the compass rose around the scope edge
+    #[serde(default = "default_true")]
+    pub show_compass_rose: bool,
+    
+    /// Time constant in seconds for the camera catching up in follow mode (0 = instant)
+    #[serde(default = "default_camera_easing")]
+    pub camera_easing_secs: f32,
+    
+    /// Follow mode starts track-up, rotating the scope to the aircraft's heading
+    #[serde(default = "default_false")]
+    pub follow_track_up: bool,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Colors take hex ("#0A0E1A", or "#0A0E1A80" with alpha), a name such as
+/// "amber", or "$name" for an entry of the palette
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ColorConfig {
-    /// Background color (hex)
+    /// Theme used instead of these colors: "night", "day", or the name of
+    /// a file in the themes folder, e.g. "dusk" for themes/dusk.toml
+    #[serde(default)]
+    pub theme: Option<String>,
+    
+    /// Background color
     #[serde(default = "default_bg_color")]
     pub background: String,
     
@@ -126,6 +221,54 @@ pub struct ColorConfig {
     /// Ground track color
     #[serde(default = "default_ground_color")]
     pub ground: String,
+    
+    /// Leader line color (tag to target)
+    #[serde(default = "default_leader_color")]
+    pub leader_line: String,
+    
+    /// Wake separation warning color
+    #[serde(default = "default_wake_color")]
+    pub wake_alert: String,
+    
+    /// Outline color of area filters
+    #[serde(default = "default_filter_area_color")]
+    pub filter_area: String,
+    
+    /// Compass rose color
+    #[serde(default = "default_compass_color")]
+    pub compass: String,
+    
+    /// Named colors the others can refer to as "$name"
+    #[serde(default)]
+    pub palette: BTreeMap<String, String>,
+    
+    /// Colors of the panels, windows and controls around the scope
+    #[serde(default)]
+    pub chrome: ChromeConfig,
+}
+
+/// Look of the egui interface; unset colors keep the look's own
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ChromeConfig {
+    /// Start from the dark look rather than the light one
+    #[serde(default = "default_true")]
+    pub dark: bool,
+    
+    /// Panel and window background
+    #[serde(default)]
+    pub background: Option<String>,
+    
+    /// Text color
+    #[serde(default)]
+    pub text: Option<String>,
+    
+    /// Button and text field background
+    #[serde(default)]
+    pub widget: Option<String>,
+    
+    /// Selection and link color
+    #[serde(default)]
+    pub accent: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -139,7 +282,13 @@ pub struct DataTagConfig {
     pub line_spacing: f32,
     
     /// Template for line 1
-    /// Available variables: {callsign}, {altitude}, {speed}, {gs}, {heading}, {type}
+    /// Available variables: {callsign}, {altitude}, {speed}, {gs}, {heading}, {type},
+    /// {dep}, {arr}, {rules}, {rfl}, {player}, {emergency}, {ground}, {cat}, {wtc}, {icao},
+    /// {wake} (gap to the aircraft ahead on final), {wake_alert} (set when below wake separation),
+    /// and the controller's annotations {ahdg}, {aspd}, {cfl} (hundreds of feet), {rwy}, {scratch}
+    /// Fields take a width spec like {gs:03}, {type:>6} or {callsign:.7};
+    /// {?var}...{/} shows text only when var is set, {!var}...{/} when it isn't.
+    /// Lines that render empty are dropped.
     #[serde(default = "default_line1")]
     pub line1: String,
     
@@ -148,12 +297,76 @@ pub struct DataTagConfig {
     pub line2: String,
     
     /// Template for line 3 (optional)
+    /// e.g. "{?wake_alert}{wake_alert} {wake}{/}" to show wake gaps on final
     #[serde(default)]
     pub line3: Option<String>,
     
     /// Template for line 4 (optional)
     #[serde(default)]
     pub line4: Option<String>,
+    
+    /// Automatically place tags around targets to avoid overlap
+    /// When disabled, tags are always drawn at `offset`
+    #[serde(default = "default_true")]
+    pub auto_placement: bool,
+    
+    /// Distance in pixels between target and tag for automatic placement
+    #[serde(default = "default_placement_gap")]
+    pub placement_gap: f32,
+    
+    /// Bias towards keeping a tag where it was last frame (higher = less jitter)
+    #[serde(default = "default_placement_stability")]
+    pub placement_stability: f32,
+    
+    /// Draw a leader line when a tag is pushed away from its target
+    #[serde(default = "default_true")]
+    pub leader_lines: bool,
+    
+    /// Named tag layouts (e.g. "limited", "full"), each with its own line templates
+    #[serde(default = "default_tag_layouts")]
+    pub layouts: BTreeMap<String, TagLayout>,
+    
+    /// Layout for traffic no rule matches (unset = line1..line4 above)
+    #[serde(default)]
+    pub default_layout: Option<String>,
+    
+    /// Rules choosing a layout per aircraft; the first match wins
+    #[serde(default = "default_tag_mode_rules")]
+    pub mode_rules: Vec<TagModeRule>,
+    
+    /// Layout a tag temporarily expands to while hovered (unset = no change)
+    #[serde(default = "default_hover_layout")]
+    pub hover_layout: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TagLayout {
+    /// Line templates, same syntax as line1..line4
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TagModeRule {
+    /// Condition the aircraft must meet
+    pub when: TagCondition,
+    
+    /// Name of the layout to use
+    pub layout: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagCondition {
+    /// Aircraft is selected
+    Selected,
+    /// Aircraft is on the ground
+    OnGround,
+    /// Aircraft is squawking emergency
+    Emergency,
+    /// Callsign matches the sidebar search filter
+    FilterMatch,
+    /// Aircraft is tracked (assumed) by this controller
+    Tracked,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -195,12 +408,15 @@ pub struct NetworkConfig {
 }
 
 // Default value functions
+fn default_version() -> u32 { CONFIG_VERSION }
 fn default_target_scale() -> f32 { 1.0 }
 fn default_target_stroke() -> f32 { 2.0 }
 fn default_font_size() -> f32 { 12.0 }
-fn default_history_length() -> usize { 20 }
+fn default_history_interval() -> f32 { 4.0 }
+fn default_history_duration() -> f32 { 80.0 }
 fn default_history_dot_size() -> f32 { 2.0 }
 fn default_vector_minutes() -> f32 { 3.0 }
+fn default_camera_easing() -> f32 { 0.4 }
 fn default_true() -> bool { true }
 fn default_false() -> bool { false }
 
@@ -212,11 +428,39 @@ fn default_tag_color() -> String { "#00FF00".to_string() }
 fn default_history_color() -> String { "#00AA00".to_string() }
 fn default_vector_color() -> String { "#0088FF".to_string() }
 fn default_ground_color() -> String { "#888888".to_string() }
+fn default_leader_color() -> String { "#00AA00".to_string() }
+fn default_wake_color() -> String { "#FF8800".to_string() }
+fn default_filter_area_color() -> String { "#334455".to_string() }
+fn default_compass_color() -> String { "#3A4A5A".to_string() }
 
 fn default_tag_offset() -> (f32, f32) { (15.0, -10.0) }
 fn default_line_spacing() -> f32 { 14.0 }
 fn default_line1() -> String { "{callsign}".to_string() }
 fn default_line2() -> String { "F{altitude:03} {gs:03}KT".to_string() }
+fn default_placement_gap() -> f32 { 12.0 }
+fn default_placement_stability() -> f32 { 150.0 }
+fn default_hover_layout() -> Option<String> { Some("full".to_string()) }
+
+fn default_tag_layouts() -> BTreeMap<String, TagLayout> {
+    let full = TagLayout {
+        lines: vec![
+            "{callsign} {emergency}".to_string(),
+            "F{altitude:03} {gs:03}KT".to_string(),
+            "{type:.12} {rules}".to_string(),
+            "{?dep}{dep}-{arr} {rfl}{/}".to_string(),
+            "{?cfl}C{cfl:03} {/}{?ahdg}H{ahdg:03} {/}{?aspd}S{aspd} {/}{rwy} {scratch}".to_string(),
+            "{?wake}{wake_alert} {wake}{/}".to_string(),
+        ],
+    };
+    BTreeMap::from([("full".to_string(), full)])
+}
+
+fn default_tag_mode_rules() -> Vec<TagModeRule> {
+    [TagCondition::Emergency, TagCondition::Selected, TagCondition::Tracked]
+        .into_iter()
+        .map(|when| TagModeRule { when, layout: "full".to_string() })
+        .collect()
+}
 
 fn default_fps() -> u32 { 60 }
 fn default_max_aircraft() -> usize { 500 }
@@ -228,18 +472,49 @@ fn default_reconnect_delay() -> u64 { 5 }
 fn default_ui_font_size() -> f32 { 13.0 }
 fn default_heading_font_size() -> f32 { 16.0 }
 
+impl Default for RadarConfig {
+    fn default() -> Self {
+        Self {
+            version: default_version(),
+            active_profile: None,
+            display: DisplayConfig::default(),
+            colors: ColorConfig::default(),
+            data_tags: DataTagConfig::default(),
+            target_rules: Vec::new(),
+            aircraft_types: Vec::new(),
+            wake: WakeConfig::default(),
+            filters: FilterConfig::default(),
+            viewports: Vec::new(),
+            views: ViewsConfig::default(),
+            keybindings: KeyBindings::default(),
+            performance: PerformanceConfig::default(),
+            network: NetworkConfig::default(),
+            fonts: FontConfig::default(),
+            api: ApiConfig::default(),
+            profile: BTreeMap::new(),
+        }
+    }
+}
+
 impl Default for DisplayConfig {
     fn default() -> Self {
         Self {
             target_scale: default_target_scale(),
             target_stroke: default_target_stroke(),
             font_size: default_font_size(),
-            history_length: default_history_length(),
+            history_interval_secs: default_history_interval(),
+            history_duration_secs: default_history_duration(),
+            history_fade: default_true(),
             history_dot_size: default_history_dot_size(),
             vector_minutes: default_vector_minutes(),
             show_vectors: default_true(),
             show_history: default_true(),
             show_tags: default_true(),
+            rotation: 0.0,
+            magnetic_variation: 0.0,
+            show_compass_rose: default_true(),
+            camera_easing_secs: default_camera_easing(),
+            follow_track_up: default_false(),
         }
     }
 }
@@ -247,6 +522,7 @@ impl Default for DisplayConfig {
 impl Default for ColorConfig {
     fn default() -> Self {
         Self {
+            theme: None,
             background: default_bg_color(),
             target: default_target_color(),
             target_selected: default_selected_color(),
@@ -255,6 +531,24 @@ impl Default for ColorConfig {
             history: default_history_color(),
             vector: default_vector_color(),
             ground: default_ground_color(),
+            leader_line: default_leader_color(),
+            wake_alert: default_wake_color(),
+            filter_area: default_filter_area_color(),
+            compass: default_compass_color(),
+            palette: BTreeMap::new(),
+            chrome: ChromeConfig::default(),
+        }
+    }
+}
+
+impl Default for ChromeConfig {
+    fn default() -> Self {
+        Self {
+            dark: default_true(),
+            background: None,
+            text: None,
+            widget: None,
+            accent: None,
         }
     }
 }
@@ -268,6 +562,95 @@ impl Default for DataTagConfig {
             line2: default_line2(),
             line3: None,
             line4: None,
+            auto_placement: default_true(),
+            placement_gap: default_placement_gap(),
+            placement_stability: default_placement_stability(),
+            leader_lines: default_true(),
+            layouts: default_tag_layouts(),
+            default_layout: None,
+            mode_rules: default_tag_mode_rules(),
+            hover_layout: default_hover_layout(),
+        }
+    }
+}
+
+impl DataTagConfig {
+    /// Template source of each configured line with its config key
+    fn line_templates(&self) -> Vec<(&'static str, &str)> {
+        let mut lines = vec![("line1", self.line1.as_str()), ("line2", self.line2.as_str())];
+        if let Some(line3) = &self.line3 {
+            lines.push(("line3", line3));
+        }
+        if let Some(line4) = &self.line4 {
+            lines.push(("line4", line4));
+        }
+        lines
+    }
+    
+    /// Pick the layout for an aircraft
+    /// `matches` reports whether the aircraft meets a rule condition
+    /// Returns `None` for the line1..line4 layout
+    pub fn layout_for(&self, matches: impl Fn(TagCondition) -> bool, hovered: bool) -> Option<&str> {
+        if hovered && let Some(layout) = &self.hover_layout {
+            return Some(layout);
+        }
+        
+        self.mode_rules
+            .iter()
+            .find(|rule| matches(rule.when))
+            .map(|rule| rule.layout.as_str())
+            .or(self.default_layout.as_deref())
+    }
+    
+    /// Parse the templates of a layout (`None` = line1..line4)
+    /// Invalid templates (only possible if validation was skipped) render literally
+    pub fn parsed_layout(&self, name: Option<&str>) -> Vec<Template> {
+        let sources: Vec<&str> = match name.and_then(|name| self.layouts.get(name)) {
+            Some(layout) => layout.lines.iter().map(String::as_str).collect(),
+            None => self.line_templates().into_iter().map(|(_, source)| source).collect(),
+        };
+        
+        sources
+            .into_iter()
+            .map(|source| Template::parse(source).unwrap_or_else(|_| Template::literal(source)))
+            .collect()
+    }
+    
+    /// Check that every template parses and every referenced layout exists
+    pub fn validate(&self, v: &mut Validator) {
+        for (key, source) in self.line_templates() {
+            v.template(key, source);
+        }
+        
+        v.range("line_spacing", self.line_spacing, 0.0..=100.0);
+        v.range("placement_gap", self.placement_gap, 0.0..=200.0);
+        v.range("placement_stability", self.placement_stability, 0.0..=1000.0);
+        
+        v.nested("layouts", |v| {
+            for (name, layout) in &self.layouts {
+                v.nested(name, |v| {
+                    v.nested("lines", |v| {
+                        for (i, source) in layout.lines.iter().enumerate() {
+                            v.template(&i.to_string(), source);
+                        }
+                    });
+                });
+            }
+        });
+        
+        let unknown = |name: &String| !self.layouts.contains_key(name);
+        v.nested("mode_rules", |v| {
+            for (i, rule) in self.mode_rules.iter().enumerate() {
+                if unknown(&rule.layout) {
+                    v.nested(i, |v| v.error("layout", format!("unknown layout \"{}\"", rule.layout)));
+                }
+            }
+        });
+        if let Some(name) = self.default_layout.as_ref().filter(|name| unknown(name)) {
+            v.error("default_layout", format!("unknown layout \"{}\"", name));
+        }
+        if let Some(name) = self.hover_layout.as_ref().filter(|name| unknown(name)) {
+            v.error("hover_layout", format!("unknown layout \"{}\"", name));
         }
     }
 }
@@ -306,53 +689,443 @@ impl Default for FontConfig {
     }
 }
 
-impl Default for RadarConfig {
-    fn default() -> Self {
-        Self {
-            display: DisplayConfig::default(),
-            colors: ColorConfig::default(),
-            data_tags: DataTagConfig::default(),
-            performance: PerformanceConfig::default(),
-            network: NetworkConfig::default(),
-            fonts: FontConfig::default(),
+impl DisplayConfig {
+    pub fn validate(&self, v: &mut Validator) {
+        v.range("target_scale", self.target_scale, 0.1..=10.0);
+        v.range("target_stroke", self.target_stroke, 0.1..=20.0);
+        v.range("font_size", self.font_size, 4.0..=72.0);
+        v.range("history_interval_secs", self.history_interval_secs, 0.1..=60.0);
+        v.range("history_duration_secs", self.history_duration_secs, 0.0..=3600.0);
+        v.range("history_dot_size", self.history_dot_size, 0.0..=20.0);
+        v.range("vector_minutes", self.vector_minutes, 0.0..=60.0);
+        v.range("rotation", self.rotation, -360.0..=360.0);
+        v.range("magnetic_variation", self.magnetic_variation, -180.0..=180.0);
+        v.range("camera_easing_secs", self.camera_easing_secs, 0.0..=10.0);
+    }
+}
+
+impl ColorConfig {
+    /// Color of a value in this config, resolving palette references
+    pub fn resolve(&self, value: &str) -> Option<Color32> {
+        match value.strip_prefix('$') {
+            Some(name) => self.palette.get(name).and_then(|entry| try_parse_color(entry)),
+            None => try_parse_color(value),
+        }
+    }
+    
+    /// Color of a value in this config
+    /// Invalid colors are drawn white; config validation reports them
+    pub fn color(&self, value: &str) -> Color32 {
+        self.resolve(value).unwrap_or(Color32::WHITE)
+    }
+    
+    /// Check the colors; palette references are checked against the validator's palette
+    pub fn validate(&self, v: &mut Validator) {
+        if let Some(theme) = &self.theme && let Err(e) = themes::load(theme) {
+            v.error("theme", format!("{:#}", e));
+        }
+        
+        v.nested("palette", |v| {
+            for (name, value) in &self.palette {
+                if value.starts_with('$') {
+                    v.error(name, "palette entries can't refer to other entries");
+                } else {
+                    v.color(name, value);
+                }
+            }
+        });
+        
+        let fields = [
+            ("background", &self.background),
+            ("target", &self.target),
+            ("target_selected", &self.target_selected),
+            ("target_emergency", &self.target_emergency),
+            ("tag_text", &self.tag_text),
+            ("history", &self.history),
+            ("vector", &self.vector),
+            ("ground", &self.ground),
+            ("leader_line", &self.leader_line),
+            ("wake_alert", &self.wake_alert),
+            ("filter_area", &self.filter_area),
+            ("compass", &self.compass),
+        ];
+        
+        for (key, value) in fields {
+            v.color(key, value);
         }
+        
+        v.nested("chrome", |v| {
+            let fields = [
+                ("background", &self.chrome.background),
+                ("text", &self.chrome.text),
+                ("widget", &self.chrome.widget),
+                ("accent", &self.chrome.accent),
+            ];
+            for (key, value) in fields {
+                if let Some(value) = value {
+                    v.color(key, value);
+                }
+            }
+        });
+    }
+}
+
+impl PerformanceConfig {
+    pub fn validate(&self, v: &mut Validator) {
+        v.range("target_fps", self.target_fps, 1..=1000);
+        v.range("max_aircraft", self.max_aircraft, 1..=100_000);
+    }
+}
+
+impl NetworkConfig {
+    pub fn validate(&self, v: &mut Validator) {
+        v.url("websocket_url", &self.websocket_url, &["ws", "wss"]);
+        v.url("api_base_url", &self.api_base_url, &["http", "https"]);
+        v.range("reconnect_delay_secs", self.reconnect_delay_secs, 1..=3600);
+    }
+}
+
+impl FontConfig {
+    pub fn validate(&self, v: &mut Validator) {
+        if let Some(size) = self.radar_font_size {
+            v.range("radar_font_size", size, 4.0..=72.0);
+        }
+        v.range("ui_font_size", self.ui_font_size, 4.0..=72.0);
+        v.range("heading_font_size", self.heading_font_size, 4.0..=72.0);
+    }
+}
+
+impl RadarConfig {
+    /// Check the configuration for mistakes serde can't catch
+    /// All problems are collected rather than stopping at the first
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut v = Validator::new();
+        
+        // Rule colors may refer to the palette of the theme in use as well
+        let theme = self.colors.theme.as_deref().and_then(|name| themes::load(name).ok());
+        v.set_palette(self.colors.palette.keys().chain(theme.iter().flat_map(|t| t.palette.keys())));
+        
+        v.nested("display", |v| self.display.validate(v));
+        v.nested("colors", |v| self.colors.validate(v));
+        v.nested("data_tags", |v| self.data_tags.validate(v));
+        v.nested("target_rules", |v| {
+            for (i, rule) in self.target_rules.iter().enumerate() {
+                v.nested(i, |v| rule.validate(v));
+            }
+        });
+        v.nested("wake", |v| self.wake.validate(v));
+        v.nested("filters", |v| self.filters.validate(v));
+        v.nested("viewports", |v| {
+            for (i, viewport) in self.viewports.iter().enumerate() {
+                v.nested(i, |v| viewport.validate(v));
+            }
+        });
+        v.nested("views", |v| self.views.validate(v));
+        v.nested("keybindings", |v| self.keybindings.validate(v));
+        v.nested("performance", |v| self.performance.validate(v));
+        v.nested("network", |v| self.network.validate(v));
+        v.nested("fonts", |v| self.fonts.validate(v));
+        v.nested("api", |v| self.api.validate(v));
+        
+        v.finish()
+    }
+    
+    /// Validate the config together with every profile it defines
+    pub fn validate_with_profiles(&self) -> Result<(), ConfigError> {
+        let mut v = Validator::new();
+        
+        if let Err(e) = self.validate() {
+            v.extend(e);
+        }
+        crate::profiles::validate(self, &mut v);
+        
+        v.finish()
+    }
+}
+
+/// Config file chosen at startup, see `set_config_path`
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use a config file other than the one in the platform config directory
+/// Only takes effect before the config path is first used.
+pub fn set_config_path(path: PathBuf) {
+    if CONFIG_PATH.set(path).is_err() {
+        tracing::warn!("Config path already in use, ignoring override");
     }
 }
 
 /// Get the config file path
 pub fn config_path() -> PathBuf {
-    PathBuf::from("config.toml")
+    CONFIG_PATH.get_or_init(default_config_path).clone()
+}
+
+/// Path of another file kept next to the config file, such as strips.json
+pub fn data_path(file_name: &str) -> PathBuf {
+    config_path()
+        .parent()
+        .map_or_else(|| PathBuf::from(file_name), |dir| dir.join(file_name))
+}
+
+/// Platform directory for FeritScope's files
+///
+/// `$XDG_CONFIG_HOME/feritscope` or `~/.config/feritscope` on Linux,
+/// `~/Library/Application Support/FeritScope` on macOS and
+/// `%APPDATA%\FeritScope` on Windows.
+pub fn config_dir() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    
+    if cfg!(target_os = "windows") {
+        var("APPDATA").map(|dir| dir.join("FeritScope"))
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| home.join("Library/Application Support/FeritScope"))
+    } else {
+        var("XDG_CONFIG_HOME")
+            .or_else(|| var("HOME").map(|home| home.join(".config")))
+            .map(|dir| dir.join("feritscope"))
+    }
+}
+
+/// config.toml in the platform config directory
+///
+/// A config.toml in the working directory is still used when the platform
+/// directory has none, so setups from before the move keep working.
+fn default_config_path() -> PathBuf {
+    let local = PathBuf::from("config.toml");
+    match config_dir().map(|dir| dir.join("config.toml")) {
+        Some(path) if path.exists() || !local.exists() => path,
+        _ => {
+            tracing::info!("Using config.toml from the working directory");
+            local
+        }
+    }
+}
+
+/// Contents of the config file as last written by `save_config`, so the
+/// watcher can tell our own saves from edits made outside the radar
+static LAST_SAVED: parking_lot::Mutex<Option<String>> = parking_lot::const_mutex(None);
+
+/// Parse and validate the contents of a config file
+pub fn parse_config(contents: &str) -> Result<RadarConfig, ConfigError> {
+    let config: RadarConfig = toml::from_str(contents)
+        .map_err(|e| ConfigError::from_toml(&e, contents))?;
+    
+    config.validate_with_profiles().map_err(|e| e.located(contents))?;
+    Ok(config)
 }
 
 /// Load configuration from file or create default
 pub fn load_config() -> Result<RadarConfig> {
     let path = config_path();
     
-    if path.exists() {
+    let config = if path.exists() {
         let contents = std::fs::read_to_string(&path)
             .context("Failed to read config.toml")?;
+        let contents = upgrade_config(&path, contents)?;
         
-        toml::from_str(&contents)
-            .context("Failed to parse config.toml")
+        parse_config(&contents)?
     } else {
         tracing::warn!("config.toml not found, creating default configuration");
         let config = RadarConfig::default();
         save_config(&config)?;
-        Ok(config)
+        config
+    };
+    
+    // Keep the schema editors use for config.toml in step with this build
+    if let Err(e) = crate::schema::write_schema(&data_path(SCHEMA_FILE)) {
+        tracing::warn!("Failed to write config schema: {:#}", e);
+    }
+    
+    Ok(config)
+}
+
+/// Upgrade a config file written by an older build in place
+///
+/// The original is kept next to it as e.g. config.toml.v1.bak before
+/// anything is changed. Returns the contents to use.
+fn upgrade_config(path: &Path, contents: String) -> Result<String> {
+    let Some((version, upgraded)) = migrations::migrate(&contents).map_err(|e| e.located(&contents))? else {
+        return Ok(contents);
+    };
+    
+    let file_name = path.file_name().map_or("config.toml".into(), |name| name.to_string_lossy());
+    let backup = path.with_file_name(format!("{}.v{}.bak", file_name, version));
+    if !backup.exists() {
+        std::fs::write(&backup, &contents)
+            .with_context(|| format!("Failed to back up config.toml to {}", backup.display()))?;
     }
+    
+    std::fs::write(path, &upgraded)
+        .context("Failed to write upgraded config.toml")?;
+    *LAST_SAVED.lock() = Some(upgraded.clone());
+    
+    tracing::info!(
+        "Upgraded config.toml from version {} to {}, the original is saved as {}",
+        version,
+        migrations::CONFIG_VERSION,
+        backup.display()
+    );
+    Ok(upgraded)
 }
 
 /// Save configuration to file
+///
+/// Values are merged into the existing file, so the user's comments,
+/// formatting and key order survive; only changed values are rewritten.
 pub fn save_config(config: &RadarConfig) -> Result<()> {
-    let contents = toml::to_string_pretty(config)
+    let path = config_path();
+    let mut updated: DocumentMut = toml::to_string_pretty(config)
+        .context("Failed to serialize config")?
+        .parse()
         .context("Failed to serialize config")?;
+    shorten_floats(updated.as_table_mut());
+    
+    // Never replace a file the user is part way through fixing
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(existing) => Some(
+            existing.parse::<DocumentMut>()
+                .context("config.toml has syntax errors, not overwriting it")?
+        ),
+        Err(_) => None,
+    };
     
-    std::fs::write(config_path(), contents)
+    let contents = match existing {
+        Some(mut document) => {
+            merge_table(document.as_table_mut(), updated.as_table());
+            document.to_string()
+        }
+        // Point editors at the schema so new files get autocomplete
+        None => format!("#:schema ./{}\n\n{}", SCHEMA_FILE, updated),
+    };
+    
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(&path, &contents)
         .context("Failed to write config.toml")?;
     
+    *LAST_SAVED.lock() = Some(contents);
     Ok(())
 }
 
+/// Update `existing` to hold the values of `updated`, keeping its decoration
+fn merge_table(existing: &mut Table, updated: &Table) {
+    existing.retain(|key, _| updated.contains_key(key));
+    
+    for (key, item) in updated.iter() {
+        let Some(current) = existing.get_mut(key) else {
+            existing.insert(key, detached(item));
+            continue;
+        };
+        
+        if !merge_item(current, item) {
+            *current = detached(item);
+            
+            // Spacing around the key suited the old kind of item, e.g. `viewports = []`
+            if let Some(mut key) = existing.key_mut(key) {
+                key.leaf_decor_mut().clear();
+            }
+        }
+    }
+}
+
+/// Merge an item of the same kind in place, returning false if the kinds differ
+fn merge_item(existing: &mut Item, updated: &Item) -> bool {
+    match (existing, updated) {
+        (Item::Table(existing), Item::Table(updated)) => merge_table(existing, updated),
+        (Item::ArrayOfTables(existing), Item::ArrayOfTables(updated)) => {
+            for (i, table) in updated.iter().enumerate() {
+                match existing.get_mut(i) {
+                    Some(current) => merge_table(current, table),
+                    None => existing.push(detached_table(table)),
+                }
+            }
+            while existing.len() > updated.len() {
+                existing.remove(existing.len() - 1);
+            }
+        }
+        (Item::Value(existing), Item::Value(updated)) => {
+            let mut bare = existing.clone();
+            bare.decor_mut().clear();
+            let same = match (&bare, updated) {
+                // Numbers as the user wrote them, e.g. 0.40 or 1, stay while they mean the same f32
+                (Value::Float(a), Value::Float(b)) => same_f32(*a.value(), *b.value()),
+                (Value::Integer(a), Value::Float(b)) => same_f32(*a.value() as f64, *b.value()),
+                _ => bare.to_string() == updated.to_string(),
+            };
+            if !same {
+                let decor = existing.decor().clone();
+                *existing = updated.clone();
+                *existing.decor_mut() = decor;
+            }
+        }
+        _ => return false,
+    }
+    
+    true
+}
+
+/// Whether two numbers read the same as f32, which most config values are
+fn same_f32(a: f64, b: f64) -> bool {
+    a as f32 == b as f32
+}
+
+/// Write floats that came from f32 values as their shortest form, e.g. 0.4
+/// rather than 0.4000000059604645
+fn shorten_floats(table: &mut Table) {
+    for (_, item) in table.iter_mut() {
+        match item {
+            Item::Value(value) => shorten_float(value),
+            Item::Table(table) => shorten_floats(table),
+            Item::ArrayOfTables(array) => array.iter_mut().for_each(shorten_floats),
+            Item::None => {}
+        }
+    }
+}
+
+fn shorten_float(value: &mut Value) {
+    match value {
+        Value::Float(float) => {
+            let single = *float.value() as f32;
+            if single as f64 == *float.value() &&
+                let Ok(shortest) = single.to_string().parse::<f64>()
+            {
+                let decor = float.decor().clone();
+                *value = Value::from(shortest);
+                *value.decor_mut() = decor;
+            }
+        }
+        Value::Array(array) => array.iter_mut().for_each(shorten_float),
+        Value::InlineTable(table) => table.iter_mut().for_each(|(_, value)| shorten_float(value)),
+        _ => {}
+    }
+}
+
+/// Copy of a new item without its position in the document it came from,
+/// so it is written next to its neighbours rather than by that position
+fn detached(item: &Item) -> Item {
+    match item {
+        Item::Table(table) => Item::Table(detached_table(table)),
+        Item::ArrayOfTables(array) => {
+            let mut copy = toml_edit::ArrayOfTables::new();
+            for table in array.iter() {
+                copy.push(detached_table(table));
+            }
+            Item::ArrayOfTables(copy)
+        }
+        item => item.clone(),
+    }
+}
+
+fn detached_table(table: &Table) -> Table {
+    let mut copy = Table::new();
+    copy.set_implicit(table.is_implicit());
+    for (key, item) in table.iter() {
+        copy.insert(key, detached(item));
+    }
+    copy
+}
+
 /// Configuration file watcher with hot-reload
 pub struct ConfigWatcher {
     state: Arc<RadarState>,
@@ -376,6 +1149,12 @@ impl ConfigWatcher {
         
         watcher.watch(config_path().as_ref(), RecursiveMode::NonRecursive)?;
         
+        // Theme files are picked up as they are edited, but the folder is only watched if it exists at startup
+        let themes = themes_dir();
+        if themes.is_dir() {
+            watcher.watch(&themes, RecursiveMode::NonRecursive)?;
+        }
+        
         tracing::info!("Config file watcher started");
         
         while let Some(res) = rx.recv().await {
@@ -386,13 +1165,49 @@ impl ConfigWatcher {
                     // Reload configuration
                     tokio::time::sleep(Duration::from_millis(100)).await;
                     
-                    match load_config() {
-                        Ok(config) => {
-                            self.state.update_config(config);
-                            tracing::info!("Configuration reloaded successfully");
+                    if event.paths.iter().all(|path| path.starts_with(&themes)) {
+                        self.state.reload_theme();
+                        tracing::info!("Theme reloaded");
+                        continue;
+                    }
+                    
+                    let contents = match std::fs::read_to_string(config_path()) {
+                        Ok(contents) => contents,
+                        Err(e) => {
+                            tracing::error!("Failed to read config.toml: {}", e);
+                            continue;
                         }
+                    };
+                    
+                    // Skip events caused by the radar saving its own changes
+                    if LAST_SAVED.lock().as_deref() == Some(contents.as_str()) {
+                        continue;
+                    }
+                    
+                    let contents = match upgrade_config(&config_path(), contents) {
+                        Ok(contents) => contents,
+                        Err(e) => {
+                            tracing::error!("Failed to upgrade config: {:#}", e);
+                            self.state.set_config_error(Some(format!("{:#}", e)));
+                            continue;
+                        }
+                    };
+                    
+                    match parse_config(&contents) {
+                        Ok(config) => match self.state.load_base_config(config) {
+                            Ok(()) => {
+                                self.state.set_config_error(None);
+                                tracing::info!("Configuration reloaded successfully");
+                            }
+                            Err(e) => {
+                                tracing::error!("Failed to apply config profile: {}", e);
+                                self.state.set_config_error(Some(e.to_string()));
+                            }
+                        },
                         Err(e) => {
+                            // Keep running on the last good configuration
                             tracing::error!("Failed to reload config: {}", e);
+                            self.state.set_config_error(Some(e.to_string()));
                         }
                     }
                 }
@@ -0,0 +1,310 @@
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+use std::time::Duration;
+use terminal_size::{Height, Width};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::state::RadarState;
+use crate::types::{FlightPhase, TrackedAircraft};
+
+/// Time between redraws
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Terminal size used when it can't be found out
+const DEFAULT_SIZE: (usize, usize) = (120, 40);
+
+/// Rows kept for the controller and ATIS lists below the aircraft table
+const STATION_ROWS: usize = 8;
+
+/// Switch to the alternate screen and hide the cursor
+const ENTER_SCREEN: &str = "\x1b[?1049h\x1b[?25l";
+
+/// Show the cursor and return to the normal screen
+const LEAVE_SCREEN: &str = "\x1b[?25h\x1b[?1049l";
+
+/// Move to the top left and clear the screen
+const CLEAR: &str = "\x1b[H\x1b[2J";
+
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[31m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Terminal view of the radar state for headless use
+///
+/// On a terminal the dashboard redraws in place every second. When stdout
+/// is redirected, as in scripted runs, plain frames without escape codes
+/// are printed whenever the content changes.
+pub struct Dashboard {
+    state: Arc<RadarState>,
+
+    /// Redraw in place with colors, rather than printing plain frames
+    interactive: bool,
+}
+
+impl Dashboard {
+    pub fn new(state: Arc<RadarState>) -> Self {
+        Self { state, interactive: Self::is_interactive() }
+    }
+
+    /// Whether the dashboard takes over the terminal, so logs must go elsewhere
+    pub fn is_interactive() -> bool {
+        std::io::stdout().is_terminal()
+    }
+
+    /// Draw until Ctrl+C or `q`, reading `a` to acknowledge wake alerts
+    pub async fn run(self) -> Result<()> {
+        let mut stdout = std::io::stdout();
+        if self.interactive {
+            write!(stdout, "{}", ENTER_SCREEN)?;
+        }
+
+        let mut commands = BufReader::new(tokio::io::stdin()).lines();
+        let mut stdin_open = true;
+        let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+        let mut last_frame = String::new();
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        let result = loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    // Plain frames are never cut, so scripts see every aircraft
+                    let size = if self.interactive { screen_size() } else { (usize::MAX, usize::MAX) };
+                    let frame = self.render(size);
+                    if self.interactive {
+                        write!(stdout, "{}{}", CLEAR, frame)?;
+                    } else if frame != last_frame {
+                        writeln!(stdout, "{}\n", frame)?;
+                    }
+                    stdout.flush()?;
+                    last_frame = frame;
+                }
+                line = commands.next_line(), if stdin_open => match line {
+                    Ok(Some(line)) => match line.trim() {
+                        "a" => self.state.acknowledge_wake_alerts(),
+                        "q" => break Ok(()),
+                        _ => {}
+                    },
+                    // No input, e.g. when started from a script
+                    Ok(None) | Err(_) => stdin_open = false,
+                },
+                result = &mut ctrl_c => break result.map_err(Into::into),
+            }
+        };
+
+        if self.interactive {
+            write!(stdout, "{}", LEAVE_SCREEN)?;
+            stdout.flush()?;
+        }
+        result
+    }
+
+    /// One frame of the dashboard, fitted to `(columns, rows)`
+    fn render(&self, (columns, rows): (usize, usize)) -> String {
+        let mut lines = Vec::new();
+        let style = |code: &'static str| if self.interactive { code } else { "" };
+
+        // Aircraft that dropped off the feed are listed after the rest until they are cleared
+        let status = self.state.get_connection_status();
+        let latest = status.last_data_received.unwrap_or_default();
+        let mut aircraft: Vec<TrackedAircraft> = self.state.get_aircraft().into_values().collect();
+        aircraft.sort_by(|a, b| {
+            (a.is_off_feed(latest), !a.info.is_emergency_occuring, &a.callsign)
+                .cmp(&(b.is_off_feed(latest), !b.info.is_emergency_occuring, &b.callsign))
+        });
+        let on_feed: Vec<&TrackedAircraft> = aircraft.iter().filter(|a| !a.is_off_feed(latest)).collect();
+
+        // Status line
+        let config = self.state.get_config();
+        let mut header = format!(
+            "{}FeritScope{}  {}  {} aircraft ({} event)",
+            style(BOLD),
+            style(RESET),
+            if status.websocket_connected { "connected" } else { "disconnected" },
+            on_feed.len(),
+            on_feed.iter().filter(|a| a.is_event).count()
+        );
+        if let Some(received) = status.last_data_received.and_then(chrono::DateTime::from_timestamp_millis) {
+            let _ = write!(header, "  last data {}", received.format("%H:%M:%SZ"));
+        }
+        if let Some(profile) = &config.active_profile {
+            let _ = write!(header, "  profile {}", profile);
+        }
+        lines.push(header);
+        if let Some(error) = self.state.get_config_error() {
+            let first = error.lines().next().unwrap_or_default();
+            lines.push(format!("{}{} (config.toml kept its last good settings){}", style(RED), first, style(RESET)));
+        }
+        lines.push(String::new());
+
+        // Alerts
+        let alerts = self.state.get_alerts();
+        lines.push(format!("{}ALERTS ({}){}", style(BOLD), alerts.len(), style(RESET)));
+        if alerts.is_empty() {
+            lines.push("  none".to_string());
+        }
+        for alert in &alerts {
+            let (color, suffix) = if alert.acknowledged { (style(DIM), "  ack") } else { (style(RED), "") };
+            lines.push(format!(
+                "{}  {:<6} {:<10} {}{}{}",
+                color,
+                alert.kind.label(),
+                alert.callsign,
+                alert.message,
+                suffix,
+                style(RESET)
+            ));
+        }
+        lines.push(String::new());
+
+        // Stations, drawn below the table but laid out first so the table gets what is left
+        let mut stations = vec![format!("{}{:<40}ATIS{}", style(BOLD), "CONTROLLERS", style(RESET))];
+        let controllers = self.controller_lines();
+        let atis = self.atis_lines();
+        for i in 0..controllers.len().max(atis.len()).min(STATION_ROWS) {
+            let controller = controllers.get(i).map_or("", String::as_str);
+            let atis = atis.get(i).map_or("", String::as_str);
+            stations.push(format!("{:<40}{}", controller, atis));
+        }
+
+        // Aircraft, emergencies first
+        let room = rows.saturating_sub(lines.len() + stations.len() + 3).max(1);
+        lines.push(format!(
+            "{}{:<10} {:<5} {:>6} {:>4} {:>4} {:>6} {:<10} {:<5} {:<10}{}",
+            style(BOLD),
+            "CALLSIGN",
+            "TYPE",
+            "ALT",
+            "GS",
+            "HDG",
+            "VS",
+            "ROUTE",
+            "RULES",
+            "PHASE",
+            style(RESET)
+        ));
+        for tracked in aircraft.iter().take(room) {
+            let off_feed = tracked.is_off_feed(latest);
+            let color = if off_feed {
+                style(DIM)
+            } else if tracked.info.is_emergency_occuring {
+                style(RED)
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "{}{}{}",
+                color,
+                aircraft_row(tracked, off_feed),
+                if color.is_empty() { "" } else { style(RESET) }
+            ));
+        }
+        if aircraft.len() > room {
+            lines.push(format!("  … {} more", aircraft.len() - room));
+        }
+        lines.push(String::new());
+        lines.extend(stations);
+
+        // Cut lines to the terminal width, keeping escape codes intact
+        let lines: Vec<String> = lines
+            .into_iter()
+            .map(|line| truncate(line.trim_end(), columns))
+            .collect();
+        lines.join("\n")
+    }
+
+    /// Claimed controller positions, by airport
+    fn controller_lines(&self) -> Vec<String> {
+        let mut controllers: Vec<_> = self.state
+            .get_controllers()
+            .into_iter()
+            .filter_map(|c| c.holder.clone().map(|holder| (c, holder)))
+            .collect();
+        controllers.sort_by(|(a, _), (b, _)| (&a.airport, &a.position).cmp(&(&b.airport, &b.position)));
+
+        controllers
+            .into_iter()
+            .map(|(c, holder)| {
+                let queue = if c.queue.is_empty() { String::new() } else { format!(" +{}", c.queue.len()) };
+                format!("  {} {:<4} {}{}", c.airport, c.position, holder, queue)
+            })
+            .collect()
+    }
+
+    /// Current ATIS letter and first line, by airport
+    fn atis_lines(&self) -> Vec<String> {
+        let mut atis: Vec<_> = self.state.get_all_atis().into_values().collect();
+        atis.sort_by(|a, b| a.airport.cmp(&b.airport));
+
+        atis.into_iter()
+            .map(|atis| {
+                let first = atis.lines.iter().find(|line| !line.trim().is_empty()).map_or("", |line| line.trim());
+                format!("{} {} {}", atis.airport, atis.letter, first)
+            })
+            .collect()
+    }
+}
+
+/// Table row of an aircraft, with "lost" as the phase once it is off the feed
+fn aircraft_row(tracked: &TrackedAircraft, off_feed: bool) -> String {
+    let plan = tracked.flight_plan.as_ref();
+    let route = plan.map_or(String::new(), |plan| format!("{}-{}", plan.departing, plan.arriving));
+    let phase = match tracked.phase() {
+        _ if off_feed => "lost",
+        FlightPhase::Ground => "ground",
+        FlightPhase::Climbing => "climbing",
+        FlightPhase::Descending => "descending",
+        FlightPhase::Level => "level",
+    };
+
+    format!(
+        "{:<10} {:<5} {:>6.0} {:>4.0} {:>4.0} {:>+6.0} {:<10} {:<5} {:<10}",
+        tracked.callsign,
+        tracked.type_info.icao,
+        tracked.info.altitude,
+        tracked.info.ground_speed,
+        tracked.info.heading,
+        tracked.vertical_speed,
+        route,
+        plan.map_or("", |plan| plan.flight_rules.as_str()),
+        phase
+    )
+}
+
+/// Cut a line to `width` visible characters, not counting escape codes
+fn truncate(line: &str, width: usize) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut visible = 0;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            out.push(c);
+            for c in chars.by_ref() {
+                out.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else if visible < width {
+            out.push(c);
+            visible += 1;
+        }
+    }
+
+    out
+}
+
+/// Terminal columns and rows, asked of the terminal or taken from the environment
+fn screen_size() -> (usize, usize) {
+    match terminal_size::terminal_size() {
+        Some((Width(columns), Height(rows))) => (columns.into(), rows.into()),
+        None => {
+            let var = |name: &str| std::env::var(name).ok().and_then(|value| value.parse().ok());
+            (var("COLUMNS").unwrap_or(DEFAULT_SIZE.0), var("LINES").unwrap_or(DEFAULT_SIZE.1))
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::TrackedAircraft;
+use crate::validation::Validator;
+
+/// What happens to aircraft outside the filtered volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterMode {
+    /// Don't draw them at all
+    Hide,
+    /// Draw them faded
+    Dim,
+}
+
+/// How an aircraft should be drawn after filtering
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Visibility {
+    Shown,
+    Dimmed,
+    Hidden,
+}
+
+/// Display filters limiting the scope to the controller's volume
+///
+/// Filters only affect drawing; filtered aircraft still take part in wake
+/// separation checks. Emergencies and the selected aircraft are always shown.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FilterConfig {
+    /// Enable the altitude filter
+    #[serde(default)]
+    pub altitude_enabled: bool,
+
+    /// Lowest altitude shown in feet
+    #[serde(default)]
+    pub altitude_floor: f64,
+
+    /// Highest altitude shown in feet
+    #[serde(default = "default_altitude_ceiling")]
+    pub altitude_ceiling: f64,
+
+    /// Polygonal areas; with any area enabled, only aircraft inside one are shown
+    #[serde(default)]
+    pub areas: Vec<AreaFilter>,
+
+    /// Hide or dim filtered aircraft
+    #[serde(default = "default_filter_mode")]
+    pub mode: FilterMode,
+
+    /// Opacity of dimmed aircraft (0.0 - 1.0)
+    #[serde(default = "default_dim_opacity")]
+    pub dim_opacity: f32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AreaFilter {
+    /// Name shown in the top panel
+    pub name: String,
+
+    /// Polygon corners in studs (x, y)
+    pub points: Vec<(f64, f64)>,
+
+    /// Whether this area is active
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool { true }
+fn default_altitude_ceiling() -> f64 { 60000.0 }
+fn default_filter_mode() -> FilterMode { FilterMode::Dim }
+fn default_dim_opacity() -> f32 { 0.25 }
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            altitude_enabled: false,
+            altitude_floor: 0.0,
+            altitude_ceiling: default_altitude_ceiling(),
+            areas: Vec::new(),
+            mode: default_filter_mode(),
+            dim_opacity: default_dim_opacity(),
+        }
+    }
+}
+
+impl FilterConfig {
+    pub fn validate(&self, v: &mut Validator) {
+        if self.altitude_floor > self.altitude_ceiling {
+            v.error("altitude_floor", "is above altitude_ceiling");
+        }
+        v.range("dim_opacity", self.dim_opacity, 0.0..=1.0);
+
+        v.nested("areas", |v| {
+            for (i, area) in self.areas.iter().enumerate() {
+                if area.points.len() < 3 {
+                    v.nested(i, |v| v.error("points", "an area needs at least 3 points"));
+                }
+            }
+        });
+    }
+
+    /// Whether an aircraft is inside the filtered volume
+    pub fn passes(&self, tracked: &TrackedAircraft) -> bool {
+        let altitude = tracked.info.altitude;
+        let in_band = !self.altitude_enabled
+            || (altitude >= self.altitude_floor && altitude <= self.altitude_ceiling);
+
+        let mut enabled_areas = self.areas.iter().filter(|area| area.enabled).peekable();
+        let in_area = enabled_areas.peek().is_none()
+            || enabled_areas.any(|area| {
+                point_in_polygon((tracked.info.position.x, tracked.info.position.y), &area.points)
+            });
+
+        in_band && in_area
+    }
+
+    /// Visibility of an aircraft, never filtering emergencies or the selected aircraft
+    pub fn visibility(&self, tracked: &TrackedAircraft, selected: bool) -> Visibility {
+        if selected || tracked.info.is_emergency_occuring || self.passes(tracked) {
+            Visibility::Shown
+        } else {
+            match self.mode {
+                FilterMode::Hide => Visibility::Hidden,
+                FilterMode::Dim => Visibility::Dimmed,
+            }
+        }
+    }
+
+    /// Whether any filter is active
+    pub fn is_active(&self) -> bool {
+        self.altitude_enabled || self.areas.iter().any(|area| area.enabled)
+    }
+}
+
+/// Ray-casting point-in-polygon test
+pub fn point_in_polygon(point: (f64, f64), polygon: &[(f64, f64)]) -> bool {
+    let (x, y) = point;
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+
+    for i in 0..polygon.len() {
+        let (xi, yi) = polygon[i];
+        let (xj, yj) = polygon[j];
+
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+
+    inside
+}
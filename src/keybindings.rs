@@ -0,0 +1,307 @@
+use anyhow::Result;
+use egui::{Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::validation::Validator;
+
+/// Something the radar can do from the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    ToggleSidebar,
+    ToggleStrips,
+    ToggleSettings,
+    ToggleVectors,
+    ToggleHistory,
+    ToggleTags,
+    ToggleCompassRose,
+    ToggleAltitudeFilter,
+    SelectNext,
+    SelectPrevious,
+    /// Stop the ruler, or clear the selection when there is no ruler
+    Cancel,
+    ZoomIn,
+    ZoomOut,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    RecallView1,
+    RecallView2,
+    RecallView3,
+    RecallView4,
+    RecallView5,
+    RecallView6,
+    RecallView7,
+    RecallView8,
+    RecallView9,
+    AcknowledgeAlerts,
+    Follow,
+    AssumeRelease,
+    RotateTag,
+    ResetTag,
+    Measure,
+    CommandLine,
+}
+
+impl Action {
+    pub const ALL: [Action; 33] = [
+        Action::ToggleSidebar,
+        Action::ToggleStrips,
+        Action::ToggleSettings,
+        Action::ToggleVectors,
+        Action::ToggleHistory,
+        Action::ToggleTags,
+        Action::ToggleCompassRose,
+        Action::ToggleAltitudeFilter,
+        Action::SelectNext,
+        Action::SelectPrevious,
+        Action::Cancel,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::PanUp,
+        Action::PanDown,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::RecallView1,
+        Action::RecallView2,
+        Action::RecallView3,
+        Action::RecallView4,
+        Action::RecallView5,
+        Action::RecallView6,
+        Action::RecallView7,
+        Action::RecallView8,
+        Action::RecallView9,
+        Action::AcknowledgeAlerts,
+        Action::Follow,
+        Action::AssumeRelease,
+        Action::RotateTag,
+        Action::ResetTag,
+        Action::Measure,
+        Action::CommandLine,
+    ];
+
+    /// Name used in the `[keybindings]` config section
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::ToggleSidebar => "toggle_sidebar",
+            Action::ToggleStrips => "toggle_strips",
+            Action::ToggleSettings => "toggle_settings",
+            Action::ToggleVectors => "toggle_vectors",
+            Action::ToggleHistory => "toggle_history",
+            Action::ToggleTags => "toggle_tags",
+            Action::ToggleCompassRose => "toggle_compass_rose",
+            Action::ToggleAltitudeFilter => "toggle_altitude_filter",
+            Action::SelectNext => "select_next",
+            Action::SelectPrevious => "select_previous",
+            Action::Cancel => "cancel",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::PanUp => "pan_up",
+            Action::PanDown => "pan_down",
+            Action::PanLeft => "pan_left",
+            Action::PanRight => "pan_right",
+            Action::RecallView1 => "recall_view_1",
+            Action::RecallView2 => "recall_view_2",
+            Action::RecallView3 => "recall_view_3",
+            Action::RecallView4 => "recall_view_4",
+            Action::RecallView5 => "recall_view_5",
+            Action::RecallView6 => "recall_view_6",
+            Action::RecallView7 => "recall_view_7",
+            Action::RecallView8 => "recall_view_8",
+            Action::RecallView9 => "recall_view_9",
+            Action::AcknowledgeAlerts => "acknowledge_alerts",
+            Action::Follow => "follow",
+            Action::AssumeRelease => "assume_release",
+            Action::RotateTag => "rotate_tag",
+            Action::ResetTag => "reset_tag",
+            Action::Measure => "measure",
+            Action::CommandLine => "command_line",
+        }
+    }
+
+    fn default_key(self) -> &'static str {
+        match self {
+            Action::ToggleSidebar => "F2",
+            Action::ToggleStrips => "F3",
+            Action::ToggleSettings => "F4",
+            Action::ToggleVectors => "V",
+            Action::ToggleHistory => "H",
+            Action::ToggleTags => "T",
+            Action::ToggleCompassRose => "C",
+            Action::ToggleAltitudeFilter => "Shift+F",
+            Action::SelectNext => "N",
+            Action::SelectPrevious => "Shift+N",
+            Action::Cancel => "Escape",
+            Action::ZoomIn => "Equals",
+            Action::ZoomOut => "Minus",
+            Action::PanUp => "Up",
+            Action::PanDown => "Down",
+            Action::PanLeft => "Left",
+            Action::PanRight => "Right",
+            Action::RecallView1 => "Ctrl+1",
+            Action::RecallView2 => "Ctrl+2",
+            Action::RecallView3 => "Ctrl+3",
+            Action::RecallView4 => "Ctrl+4",
+            Action::RecallView5 => "Ctrl+5",
+            Action::RecallView6 => "Ctrl+6",
+            Action::RecallView7 => "Ctrl+7",
+            Action::RecallView8 => "Ctrl+8",
+            Action::RecallView9 => "Ctrl+9",
+            Action::AcknowledgeAlerts => "Space",
+            Action::Follow => "F",
+            Action::AssumeRelease => "A",
+            Action::RotateTag => "R",
+            Action::ResetTag => "Shift+R",
+            Action::Measure => "M",
+            Action::CommandLine => "Period",
+        }
+    }
+
+    /// View preset index recalled by this action
+    pub fn view_index(self) -> Option<usize> {
+        let first = Action::ALL.iter().position(|a| *a == Action::RecallView1)?;
+        let index = Action::ALL.iter().position(|a| *a == self)?.checked_sub(first)?;
+        (index < 9).then_some(index)
+    }
+
+    /// Action recalling a view preset by index
+    pub fn recall_view(index: usize) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.view_index() == Some(index))
+    }
+
+    /// Whether holding the key repeats the action
+    pub fn repeats(self) -> bool {
+        matches!(
+            self,
+            Action::ZoomIn | Action::ZoomOut | Action::PanUp | Action::PanDown | Action::PanLeft | Action::PanRight
+        )
+    }
+}
+
+impl Serialize for Action {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Action {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Action::ALL
+            .into_iter()
+            .find(|action| action.name() == name)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown keybinding action \"{}\"", name)))
+    }
+}
+
+/// Parse a key combination such as "Ctrl+Shift+R", "F2" or "Period"
+///
+/// Ctrl also matches Cmd on macOS. An empty string means unbound.
+pub fn parse_shortcut(source: &str) -> Result<Option<KeyboardShortcut>> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Ok(None);
+    }
+
+    let mut parts: Vec<&str> = source.split('+').map(str::trim).collect();
+    let key_name = parts.pop().unwrap_or_default();
+
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "cmd" | "command" => modifiers = modifiers | Modifiers::COMMAND,
+            "shift" => modifiers = modifiers | Modifiers::SHIFT,
+            "alt" | "option" => modifiers = modifiers | Modifiers::ALT,
+            _ => anyhow::bail!("unknown modifier \"{}\" in \"{}\"", part, source),
+        }
+    }
+
+    let key = Key::from_name(key_name)
+        .ok_or_else(|| anyhow::anyhow!("unknown key \"{}\" in \"{}\"", key_name, source))?;
+
+    Ok(Some(KeyboardShortcut::new(modifiers, key)))
+}
+
+/// Key combination for each action
+///
+/// Actions missing from the config keep their default key.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(from = "BTreeMap<Action, String>", into = "BTreeMap<Action, String>")]
+pub struct KeyBindings {
+    bindings: BTreeMap<Action, String>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_key().to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl From<BTreeMap<Action, String>> for KeyBindings {
+    fn from(overrides: BTreeMap<Action, String>) -> Self {
+        let mut bindings = KeyBindings::default();
+        bindings.bindings.extend(overrides);
+        bindings
+    }
+}
+
+impl From<KeyBindings> for BTreeMap<Action, String> {
+    fn from(bindings: KeyBindings) -> Self {
+        bindings.bindings
+    }
+}
+
+impl KeyBindings {
+    /// Key combination bound to an action, as written in the config
+    pub fn get(&self, action: Action) -> &str {
+        self.bindings.get(&action).map(String::as_str).unwrap_or_default()
+    }
+
+    /// Readable key combination for menus, or None if unbound
+    pub fn label(&self, action: Action) -> Option<String> {
+        parse_shortcut(self.get(action))
+            .ok()
+            .flatten()
+            .map(|shortcut| shortcut.format(&egui::ModifierNames::NAMES, cfg!(target_os = "macos")))
+    }
+
+    /// Parsed shortcuts of bound actions
+    pub fn shortcuts(&self) -> Vec<(Action, KeyboardShortcut)> {
+        self.bindings
+            .iter()
+            .filter_map(|(action, source)| Some((*action, parse_shortcut(source).ok()??)))
+            .collect()
+    }
+
+    /// Check every binding parses and no key combination is bound twice
+    pub fn validate(&self, v: &mut Validator) {
+        let mut seen: HashMap<KeyboardShortcut, Action> = HashMap::new();
+
+        for (action, source) in &self.bindings {
+            let shortcut = match parse_shortcut(source) {
+                Ok(Some(shortcut)) => shortcut,
+                Ok(None) => continue,
+                Err(e) => {
+                    v.error(action.name(), e.to_string());
+                    continue;
+                }
+            };
+
+            // Blame whichever of the two was changed from its default
+            if let Some(other) = seen.insert(shortcut, *action) {
+                let (changed, kept) = if source == action.default_key() {
+                    (other, *action)
+                } else {
+                    (*action, other)
+                };
+                v.error(changed.name(), format!("\"{}\" is already bound to {}", source, kept.name()));
+            }
+        }
+    }
+}
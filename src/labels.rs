@@ -0,0 +1,280 @@
+use anyhow::{Context, Result};
+use egui::{Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use crate::config::DataTagConfig;
+use crate::retention::{with_last_seen, Kept};
+
+/// Standard data tag positions around a target symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagPosition {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl TagPosition {
+    /// All positions, clockwise from north
+    pub const ALL: [TagPosition; 8] = [
+        TagPosition::N,
+        TagPosition::NE,
+        TagPosition::E,
+        TagPosition::SE,
+        TagPosition::S,
+        TagPosition::SW,
+        TagPosition::W,
+        TagPosition::NW,
+    ];
+
+    /// Unit direction of this position in screen space (+y is down)
+    pub fn direction(self) -> Vec2 {
+        match self {
+            TagPosition::N => Vec2::new(0.0, -1.0),
+            TagPosition::NE => Vec2::new(1.0, -1.0),
+            TagPosition::E => Vec2::new(1.0, 0.0),
+            TagPosition::SE => Vec2::new(1.0, 1.0),
+            TagPosition::S => Vec2::new(0.0, 1.0),
+            TagPosition::SW => Vec2::new(-1.0, 1.0),
+            TagPosition::W => Vec2::new(-1.0, 0.0),
+            TagPosition::NW => Vec2::new(-1.0, -1.0),
+        }
+    }
+
+    /// Closest standard position to an arbitrary screen offset
+    pub fn from_offset(offset: Vec2) -> Self {
+        let angle = offset.x.atan2(-offset.y).to_degrees().rem_euclid(360.0);
+        let index = ((angle + 22.5) / 45.0) as usize % 8;
+        Self::ALL[index]
+    }
+
+    /// Next position clockwise
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % 8]
+    }
+
+    /// Offset of a tag's top-left corner from its target at this position
+    pub fn offset(self, size: Vec2, gap: f32) -> Vec2 {
+        self.rect(Pos2::ZERO, size, gap).min.to_vec2()
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|p| *p == self).unwrap_or(0)
+    }
+
+    /// Number of 45° steps between two positions (0-4)
+    fn steps_from(self, other: TagPosition) -> usize {
+        let diff = (self.index() + 8 - other.index()) % 8;
+        diff.min(8 - diff)
+    }
+
+    /// Screen rect for a tag of `size` placed `gap` pixels from `anchor`
+    pub fn rect(self, anchor: Pos2, size: Vec2, gap: f32) -> Rect {
+        let dir = self.direction();
+
+        // Align the near edge/corner of the tag with the gap point
+        let min_x = match dir.x {
+            x if x > 0.0 => anchor.x + gap,
+            x if x < 0.0 => anchor.x - gap - size.x,
+            _ => anchor.x - size.x / 2.0,
+        };
+        let min_y = match dir.y {
+            y if y > 0.0 => anchor.y + gap,
+            y if y < 0.0 => anchor.y - gap - size.y,
+            _ => anchor.y - size.y / 2.0,
+        };
+
+        Rect::from_min_size(Pos2::new(min_x, min_y), size)
+    }
+}
+
+/// A tag waiting to be placed
+pub struct LabelRequest {
+    pub callsign: String,
+    /// Target position on screen
+    pub anchor: Pos2,
+    /// Laid-out tag size in pixels
+    pub size: Vec2,
+}
+
+/// Final placement of a single tag
+#[derive(Debug, Clone, Copy)]
+pub struct PlacedLabel {
+    pub rect: Rect,
+    /// Whether the tag was pushed away far enough to need a leader line
+    pub leader: bool,
+}
+
+/// Chosen slot for a callsign, remembered between frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Slot {
+    position: TagPosition,
+    ring: usize,
+}
+
+/// Greedy label placement engine with frame-to-frame hysteresis
+#[derive(Default)]
+pub struct LabelPlacer {
+    previous: HashMap<String, Slot>,
+}
+
+/// Number of distance rings tried around each target
+const RINGS: usize = 3;
+
+impl LabelPlacer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Place all tags, avoiding other tags, targets and the screen edge
+    ///
+    /// Requests are placed in order, so callers should put the most important
+    /// tags (e.g. the selected aircraft) first. `fixed` holds tags that were
+    /// positioned manually and must be avoided but not moved.
+    pub fn place(
+        &mut self,
+        requests: &[LabelRequest],
+        fixed: &[Rect],
+        targets: &[Pos2],
+        target_size: f32,
+        screen: Rect,
+        config: &DataTagConfig,
+    ) -> HashMap<String, PlacedLabel> {
+        let preferred = TagPosition::from_offset(Vec2::new(config.offset.0, config.offset.1));
+        let target_rects: Vec<Rect> = targets
+            .iter()
+            .map(|pos| Rect::from_center_size(*pos, Vec2::splat(target_size * 2.0)))
+            .collect();
+
+        let mut placed: Vec<Rect> = Vec::with_capacity(requests.len() + fixed.len());
+        placed.extend_from_slice(fixed);
+        let mut result = HashMap::with_capacity(requests.len());
+        let mut chosen = HashMap::with_capacity(requests.len());
+
+        for request in requests {
+            let previous = self.previous.get(&request.callsign).copied();
+            let mut best: Option<(Slot, Rect, f32)> = None;
+
+            for ring in 0..RINGS {
+                let gap = config.placement_gap * (ring + 1) as f32;
+
+                for position in TagPosition::ALL {
+                    let slot = Slot { position, ring };
+                    let rect = position.rect(request.anchor, request.size, gap);
+
+                    let mut cost = placed
+                        .iter()
+                        .map(|other| overlap_area(&rect, other))
+                        .sum::<f32>();
+
+                    cost += target_rects
+                        .iter()
+                        .map(|other| overlap_area(&rect, other))
+                        .sum::<f32>()
+                        * 2.0;
+
+                    // Keep tags on screen
+                    cost += (rect.area() - overlap_area(&rect, &screen)) * 4.0;
+
+                    // Prefer the configured direction and staying close to the target
+                    cost += position.steps_from(preferred) as f32 * 10.0;
+                    cost += ring as f32 * 60.0;
+
+                    // Hysteresis: only move if the new slot is clearly better
+                    if previous == Some(slot) {
+                        cost -= config.placement_stability;
+                    }
+
+                    if best.as_ref().is_none_or(|(_, _, best_cost)| cost < *best_cost) {
+                        best = Some((slot, rect, cost));
+                    }
+                }
+            }
+
+            if let Some((slot, rect, _)) = best {
+                placed.push(rect);
+                chosen.insert(request.callsign.clone(), slot);
+                result.insert(
+                    request.callsign.clone(),
+                    PlacedLabel {
+                        rect,
+                        leader: slot.ring > 0,
+                    },
+                );
+            }
+        }
+
+        // Forget aircraft that are no longer displayed
+        self.previous = chosen;
+
+        result
+    }
+}
+
+fn overlap_area(a: &Rect, b: &Rect) -> f32 {
+    let overlap = a.intersect(*b);
+    if overlap.is_positive() {
+        overlap.area()
+    } else {
+        0.0
+    }
+}
+
+/// Point on the edge of `rect` closest to `anchor`, used as leader line end
+pub fn leader_end(rect: &Rect, anchor: Pos2) -> Pos2 {
+    rect.clamp(anchor)
+}
+
+/// Get the tag offsets file path
+pub fn tag_offsets_path() -> PathBuf {
+    crate::config::data_path("tag_offsets.json")
+}
+
+/// Dragged tag offset as saved, in pixels from the target
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+struct SavedOffset {
+    x: f32,
+    y: f32,
+}
+
+/// Load saved tag offsets, or none if there is no offsets file yet
+pub fn load_tag_offsets() -> Result<HashMap<String, Kept<(f32, f32)>>> {
+    let path = tag_offsets_path();
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .context("Failed to read tag_offsets.json")?;
+    let saved: HashMap<String, Kept<SavedOffset>> = serde_json::from_str(&contents)
+        .context("Failed to parse tag_offsets.json")?;
+
+    Ok(saved
+        .into_iter()
+        .map(|(callsign, Kept { value, last_seen })| (callsign, Kept { value: (value.x, value.y), last_seen }))
+        .collect())
+}
+
+/// Save tag offsets to file, with when their aircraft were last seen
+pub fn save_tag_offsets(offsets: &HashMap<String, (f32, f32)>, last_seen: &HashMap<String, i64>) -> Result<()> {
+    let saved: BTreeMap<String, Kept<SavedOffset>> = with_last_seen(offsets, last_seen)
+        .into_iter()
+        .map(|(callsign, Kept { value: (x, y), last_seen })| (callsign, Kept { value: SavedOffset { x, y }, last_seen }))
+        .collect();
+
+    let contents = serde_json::to_string_pretty(&saved)
+        .context("Failed to serialize tag offsets")?;
+
+    std::fs::write(tag_offsets_path(), contents)
+        .context("Failed to write tag_offsets.json")?;
+
+    Ok(())
+}
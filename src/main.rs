@@ -1,9 +1,32 @@
+mod aircraft_db;
+mod alerts;
+mod api;
+mod annotations;
+mod cli;
+mod commands;
 mod config;
+mod dashboard;
+mod filters;
+mod keybindings;
+mod labels;
+mod migrations;
 mod network;
+mod profiles;
 mod radar;
+mod retention;
+mod rules;
+mod schema;
+mod settings;
 mod state;
+mod strips;
+mod template;
+mod themes;
 mod types;
 mod ui;
+mod validation;
+mod viewport;
+mod views;
+mod wake;
 
 use anyhow::Result;
 use eframe::egui;
@@ -11,32 +34,94 @@ use std::sync::Arc;
 use tokio::runtime::Runtime;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use crate::api::ApiServer;
 use crate::config::ConfigWatcher;
+use crate::dashboard::Dashboard;
 use crate::network::NetworkManager;
 use crate::state::RadarState;
 use crate::ui::RadarApp;
 
 fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "atc24_radar=debug,info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let args = match cli::from_env()? {
+        cli::Invocation::Run(args) => args,
+        cli::Invocation::Help => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        cli::Invocation::Version => {
+            println!("feritscope {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+    };
+
+    if let Some(path) = &args.config {
+        config::set_config_path(path.clone());
+    }
+
+    // Initialize logging, to a file while the dashboard has the terminal
+    let filter = tracing_subscriber::EnvFilter::try_new(&args.log_level)?;
+    if args.headless && Dashboard::is_interactive() {
+        let path = config::data_path("feritscope.log");
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(std::sync::Mutex::new(file)))
+            .init();
+    } else {
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+            .init();
+    }
 
     tracing::info!("Starting ATC 24 Radar Client");
+    tracing::info!("Using config file {}", config::config_path().display());
 
     // Create Tokio runtime for async operations
     let runtime = Arc::new(Runtime::new()?);
 
     // Initialize shared state
     let radar_state = Arc::new(RadarState::new());
+    radar_state.set_profile_override(args.profile.clone());
     
-    // Load initial configuration
-    let config = config::load_config()?;
-    tracing::info!("Configuration loaded successfully");
+    // Load initial configuration, starting on defaults if the file has problems
+    let config = match config::load_config() {
+        Ok(config) => {
+            tracing::info!("Configuration loaded successfully");
+            config
+        }
+        Err(e) => {
+            tracing::error!("Failed to load config: {:#}", e);
+            radar_state.set_config_error(Some(format!("{:#}", e)));
+            config::RadarConfig::default()
+        }
+    };
+    if let Some(Some(name)) = &args.profile && !config.profile.contains_key(name) {
+        tracing::error!("Unknown profile \"{}\", using the profile set in the config file", name);
+        radar_state.set_profile_override(None);
+    }
+    if let Err(e) = radar_state.load_base_config(config) {
+        tracing::error!("Failed to apply config profile: {}", e);
+        radar_state.set_config_error(Some(e.to_string()));
+    }
+    let config = radar_state.get_config();
+
+    // Restore flight strips, annotations and tag offsets from the last session
+    match strips::load_strips() {
+        Ok(strips) => radar_state.set_strips(strips),
+        Err(e) => tracing::error!("Failed to load flight strips: {}", e),
+    }
+    match annotations::load_annotations() {
+        Ok(annotations) => radar_state.set_annotations(annotations),
+        Err(e) => tracing::error!("Failed to load annotations: {}", e),
+    }
+    match labels::load_tag_offsets() {
+        Ok(offsets) => radar_state.set_tag_offsets(offsets),
+        Err(e) => tracing::error!("Failed to load tag offsets: {}", e),
+    }
 
     // Start config file watcher
     let config_watcher = ConfigWatcher::new(radar_state.clone());
@@ -46,11 +131,34 @@ fn main() -> Result<()> {
         }
     });
 
-    // Start network manager for WebSocket and REST API
+    // Start network manager for WebSocket and REST API, or play back a recording
     let network_manager = NetworkManager::new(radar_state.clone());
-    runtime.spawn(async move {
-        network_manager.run().await;
-    });
+    match args.replay.clone() {
+        Some(path) => runtime.spawn(async move {
+            if let Err(e) = network_manager.replay(&path).await {
+                tracing::error!("Replay error: {:#}", e);
+            }
+        }),
+        None => runtime.spawn(async move {
+            network_manager.run().await;
+        }),
+    };
+
+    // Local API for other tools, when enabled
+    if config.api.enabled {
+        let api_server = ApiServer::new(radar_state.clone());
+        runtime.spawn(async move {
+            if let Err(e) = api_server.run().await {
+                tracing::error!("API server error: {:#}", e);
+            }
+        });
+    }
+
+    // Terminal dashboard instead of the window, for servers and SSH sessions
+    if args.headless {
+        tracing::info!("Running headless, press Ctrl+C or enter q to stop");
+        return runtime.block_on(Dashboard::new(radar_state).run());
+    }
 
     // Configure and run the GUI
     let native_options = eframe::NativeOptions {
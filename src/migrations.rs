@@ -0,0 +1,92 @@
+use toml_edit::{value, DocumentMut, Item, TableLike};
+
+use crate::validation::ConfigError;
+
+/// Config format version written by this build
+///
+/// Files without a `version` key are version 1, the format from before
+/// versioning was added.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Seconds between history dots in version 1, which sampled on every feed update
+const V1_HISTORY_INTERVAL_SECS: f64 = 4.0;
+
+/// Step upgrading a config file by one version
+struct Migration {
+    /// Version the step upgrades from
+    from: u32,
+
+    /// Change the step makes to a table holding a config or a profile's overrides
+    apply: fn(&mut dyn TableLike),
+}
+
+/// Upgrade steps, oldest first
+const MIGRATIONS: &[Migration] = &[
+    Migration { from: 1, apply: history_length_to_duration },
+];
+
+/// Version 2: `display.history_length` (a dot count) became `display.history_duration_secs`
+fn history_length_to_duration(config: &mut dyn TableLike) {
+    let Some(display) = config.get_mut("display").and_then(Item::as_table_like_mut) else {
+        return;
+    };
+    let Some(length) = display.remove("history_length").and_then(|item| item.as_integer()) else {
+        return;
+    };
+
+    if !display.contains_key("history_duration_secs") {
+        display.insert("history_duration_secs", value((length as f64) * V1_HISTORY_INTERVAL_SECS));
+    }
+}
+
+/// Upgrade a config file written by an older build
+///
+/// Returns the version the file was at and its upgraded contents, or `None`
+/// when it is already current. Comments and formatting are kept. Files that
+/// don't parse are left for `parse_config` to report.
+pub fn migrate(contents: &str) -> Result<Option<(u32, String)>, ConfigError> {
+    let Ok(mut document) = contents.parse::<DocumentMut>() else {
+        return Ok(None);
+    };
+
+    let version = match document.get("version") {
+        None => 1,
+        Some(item) => item
+            .as_integer()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| *version >= 1)
+            .ok_or_else(|| ConfigError::new("version", "must be a whole number from 1"))?,
+    };
+
+    if version > CONFIG_VERSION {
+        return Err(
+            ConfigError::new(
+                "version",
+                format!("written by a newer FeritScope (version {}), this one reads up to version {}", version, CONFIG_VERSION)
+            )
+        );
+    }
+    if version == CONFIG_VERSION {
+        return Ok(None);
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= version) {
+        (migration.apply)(document.as_table_mut());
+
+        // Profiles hold partial configs in the same format
+        if let Some(profiles) = document.get_mut("profile").and_then(Item::as_table_like_mut) {
+            for (_, profile) in profiles.iter_mut() {
+                if let Some(profile) = profile.as_table_like_mut() {
+                    (migration.apply)(profile);
+                }
+            }
+        }
+    }
+
+    // Keep `version` at the top of the file
+    let root = document.as_table_mut();
+    root.insert("version", value(CONFIG_VERSION as i64));
+    root.sort_values_by(|a, _, b, _| (a.get() != "version").cmp(&(b.get() != "version")));
+
+    Ok(Some((version, document.to_string())))
+}
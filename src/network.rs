@@ -1,5 +1,6 @@
 use anyhow::{ Context, Result };
 use futures_util::{ SinkExt, StreamExt };
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time;
@@ -8,6 +9,9 @@ use tokio_tungstenite::{ connect_async, tungstenite::Message };
 use crate::state::RadarState;
 use crate::types::{ AircraftDataMap, Atis, ControllerPosition, FlightPlan, WsMessage };
 
+/// Pause between aircraft updates when replaying a recorded feed
+const REPLAY_INTERVAL: Duration = Duration::from_secs(3);
+
 /// Network manager for WebSocket and REST API communication
 pub struct NetworkManager {
     state: Arc<RadarState>,
@@ -43,6 +47,47 @@ impl NetworkManager {
         }
     }
 
+    /// Play back a recorded feed instead of connecting
+    ///
+    /// The file holds one feed message per line, as received from the WebSocket.
+    /// Aircraft updates are spaced `REPLAY_INTERVAL` apart; the final state stays
+    /// on screen once the file ends.
+    pub async fn replay(self, path: &Path) -> Result<()> {
+        let contents = tokio::fs
+            ::read_to_string(path).await
+            .with_context(|| format!("Failed to read replay file {}", path.display()))?;
+
+        self.state.set_websocket_connected(true);
+        tracing::info!("Replaying feed from {}", path.display());
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let msg: WsMessage = match serde_json::from_str(line) {
+                Ok(msg) => msg,
+                Err(e) => {
+                    tracing::error!("Replay line {}: not a feed message: {}", number + 1, e);
+                    continue;
+                }
+            };
+            let aircraft_update = matches!(msg.t.as_str(), "ACFT_DATA" | "EVENT_ACFT_DATA");
+
+            if let Err(e) = self.apply_message(msg) {
+                tracing::error!("Replay line {}: {}", number + 1, e);
+            }
+            if aircraft_update {
+                time::sleep(REPLAY_INTERVAL).await;
+            }
+        }
+
+        tracing::info!("Replay finished");
+        self.state.set_websocket_connected(false);
+        Ok(())
+    }
+
     /// Connect to WebSocket and handle messages
     async fn connect_websocket(&self, url: &str) -> Result<()> {
         let (ws_stream, _) = connect_async(url).await.context("Failed to connect to WebSocket")?;
@@ -101,13 +146,18 @@ impl NetworkManager {
             ::from_str(text)
             .context("Failed to parse WebSocket message")?;
 
+        self.apply_message(msg)
+    }
+
+    /// Update the radar state from a feed message
+    fn apply_message(&self, msg: WsMessage) -> Result<()> {
         match msg.t.as_str() {
             "ACFT_DATA" => {
                 let aircraft: AircraftDataMap = serde_json
                     ::from_value(msg.d)
                     .context("Failed to parse aircraft data")?;
 
-                self.state.update_aircraft_batch(aircraft);
+                self.state.update_aircraft_batch(aircraft, false);
             }
 
             "EVENT_ACFT_DATA" => {
@@ -117,7 +167,7 @@ impl NetworkManager {
                         ::from_value(msg.d)
                         .context("Failed to parse event aircraft data")?;
 
-                    self.state.update_aircraft_batch(aircraft);
+                    self.state.update_aircraft_batch(aircraft, true);
                 }
             }
 
@@ -153,11 +203,13 @@ impl NetworkManager {
 }
 
 /// REST API client for polling endpoints
+#[allow(dead_code)] // not wired up yet, the WebSocket feed covers everything we use
 pub struct RestClient {
     base_url: String,
     client: reqwest::Client,
 }
 
+#[allow(dead_code)]
 impl RestClient {
     pub fn new(base_url: String) -> Self {
         Self {
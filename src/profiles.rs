@@ -0,0 +1,198 @@
+use toml::{Table, Value};
+
+use crate::config::RadarConfig;
+use crate::validation::{ConfigError, Validator};
+
+/// Key in a profile table naming the profile it builds on
+const INHERITS: &str = "inherits";
+
+/// Keys of the base config a profile can't override
+const BASE_ONLY: [&str; 3] = ["version", "profile", "active_profile"];
+
+fn to_table(config: &RadarConfig) -> Result<Table, ConfigError> {
+    Table::try_from(config).map_err(|e| ConfigError::new("", e.to_string()))
+}
+
+/// Profile names from `name` back to the one that inherits from the base config
+pub fn chain<'a>(base: &'a RadarConfig, name: &'a str) -> Result<Vec<&'a str>, ConfigError> {
+    let mut chain = Vec::new();
+    let mut next = Some(name);
+
+    while let Some(name) = next {
+        let Some(profile) = base.profile.get(name) else {
+            let path = chain.last().map_or(String::new(), |p| format!("profile.{}.{}", p, INHERITS));
+            return Err(ConfigError::new(&path, format!("unknown profile \"{}\"", name)));
+        };
+        if chain.contains(&name) {
+            chain.push(name);
+            return Err(ConfigError::new(
+                &format!("profile.{}.{}", chain[0], INHERITS),
+                format!("profiles inherit in a loop: {}", chain.join(" → "))
+            ));
+        }
+        chain.push(name);
+
+        next = match profile.get(INHERITS) {
+            Some(Value::String(parent)) => Some(parent.as_str()),
+            Some(_) => {
+                return Err(ConfigError::new(&format!("profile.{}.{}", name, INHERITS), "must be a profile name"));
+            }
+            None => None,
+        };
+    }
+
+    Ok(chain)
+}
+
+/// Overrides of a profile, without its `inherits` key
+fn overrides(base: &RadarConfig, name: &str) -> Table {
+    let mut table = base.profile.get(name).cloned().unwrap_or_default();
+    table.remove(INHERITS);
+    table
+}
+
+/// Copy `overlay` onto `table`, merging nested tables key by key
+fn merge(table: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (table.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(value)) => merge(existing, value),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Base config with a profile, and the profiles it inherits from, applied
+pub fn with_profile(base: &RadarConfig, name: &str) -> Result<RadarConfig, ConfigError> {
+    let mut table = to_table(base)?;
+    for profile in chain(base, name)?.into_iter().rev() {
+        merge(&mut table, overrides(base, profile));
+    }
+
+    table
+        .try_into()
+        .map_err(|e: toml::de::Error| ConfigError::new(&format!("profile.{}", name), e.message()))
+}
+
+/// The config in use: the base config with its active profile applied
+pub fn effective(base: &RadarConfig) -> Result<RadarConfig, ConfigError> {
+    match &base.active_profile {
+        Some(name) => with_profile(base, name),
+        None => Ok(base.clone()),
+    }
+}
+
+/// Values of `edited` that differ from `parent`, plus every key `existing` already sets
+fn diff(edited: &Table, parent: &Table, existing: Option<&Table>) -> Table {
+    let mut changes = Table::new();
+
+    for (key, value) in edited {
+        let kept = existing.and_then(|e| e.get(key));
+        match (value, parent.get(key)) {
+            (Value::Table(value), Some(Value::Table(parent))) => {
+                let nested = diff(value, parent, kept.and_then(Value::as_table));
+                if !nested.is_empty() {
+                    changes.insert(key.clone(), Value::Table(nested));
+                }
+            }
+            (value, parent) if kept.is_some() || parent != Some(value) => {
+                changes.insert(key.clone(), value.clone());
+            }
+            _ => {}
+        }
+    }
+
+    changes
+}
+
+/// Base config to save after the in-use config was edited
+///
+/// With a profile active, edits become overrides in that profile; values
+/// can only be overridden, so removing an optional setting is not recorded.
+/// The base config's own `active_profile` is kept either way.
+pub fn store_edits(base: &RadarConfig, edited: &RadarConfig) -> Result<RadarConfig, ConfigError> {
+    let Some(name) = &edited.active_profile else {
+        let mut stored = edited.clone();
+        stored.active_profile = base.active_profile.clone();
+        return Ok(stored);
+    };
+
+    let profile = base.profile.get(name).cloned().unwrap_or_default();
+    let parent = match profile.get(INHERITS).and_then(Value::as_str) {
+        Some(parent) => to_table(&with_profile(base, parent)?)?,
+        None => to_table(base)?,
+    };
+
+    let mut changes = diff(&to_table(edited)?, &parent, Some(&profile));
+    for key in BASE_ONLY {
+        changes.remove(key);
+    }
+    if let Some(inherits) = profile.get(INHERITS) {
+        changes.insert(INHERITS.to_string(), inherits.clone());
+    }
+
+    let mut stored = base.clone();
+    stored.profile.insert(name.clone(), changes);
+    Ok(stored)
+}
+
+/// Dotted keys a profile overrides itself, not counting inherited ones
+pub fn overridden_keys(base: &RadarConfig, name: &str) -> Vec<String> {
+    fn walk(table: &Table, prefix: &str, keys: &mut Vec<String>) {
+        for (key, value) in table {
+            let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+            match value {
+                Value::Table(nested) => walk(nested, &path, keys),
+                _ => keys.push(path),
+            }
+        }
+    }
+
+    let mut keys = Vec::new();
+    walk(&overrides(base, name), "", &mut keys);
+    keys
+}
+
+/// Check that the active profile exists and every profile gives a valid config
+pub fn validate(base: &RadarConfig, v: &mut Validator) {
+    if let Some(name) = &base.active_profile && !base.profile.contains_key(name) {
+        v.error("active_profile", format!("unknown profile \"{}\"", name));
+    }
+
+    for (name, table) in &base.profile {
+        if let Some(key) = BASE_ONLY.iter().find(|key| table.contains_key(**key)) {
+            v.nested("profile", |v| v.nested(name, |v| v.error(key, "can only be set in the base config")));
+            continue;
+        }
+
+        let config = match with_profile(base, name) {
+            Ok(config) => config,
+            Err(e) => {
+                v.extend(e);
+                continue;
+            }
+        };
+
+        // Only report problems in values this profile sets; the rest are the base config's
+        if let Err(e) = config.validate() {
+            let keys = overridden_keys(base, name);
+            v.nested("profile", |v| {
+                v.nested(name, |v| {
+                    v.extend(ConfigError {
+                        issues: e.issues
+                            .into_iter()
+                            .filter(|issue| {
+                                keys.iter().any(|key| {
+                                    issue.path == *key ||
+                                        issue.path.starts_with(&format!("{}.", key)) ||
+                                        key.starts_with(&format!("{}.", issue.path))
+                                })
+                            })
+                            .collect(),
+                    });
+                });
+            });
+        }
+    }
+}
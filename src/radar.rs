@@ -1,8 +1,14 @@
-use egui::{Color32, Pos2, Stroke, Vec2};
-use std::collections::HashMap;
+use egui::{Color32, Pos2, Rect, Stroke, Vec2};
+use std::collections::{HashMap, HashSet};
 
-use crate::config::{ColorConfig, DisplayConfig};
+use crate::annotations::Annotation;
+use crate::config::{ColorConfig, DisplayConfig, RadarConfig, TagCondition};
+use crate::filters::Visibility;
+use crate::labels::{leader_end, LabelPlacer, LabelRequest, TagPosition};
+use crate::rules::{style_for, TargetSymbol};
+use crate::template::{TagContext, Template};
 use crate::types::TrackedAircraft;
+use crate::wake::WakeAdvisory;
 
 /// Coordinate projection system for converting PTFS studs to screen pixels
 #[derive(Debug, Clone)]
@@ -13,6 +19,12 @@ pub struct Projection {
     /// Zoom level (studs per pixel)
     pub studs_per_pixel: f64,
     
+    /// True heading shown at the top of the scope in degrees (0 = north up)
+    pub rotation: f64,
+    
+    /// Top-left corner of the scope on screen
+    pub origin: Pos2,
+    
     /// Screen dimensions
     pub screen_width: f32,
     pub screen_height: f32,
@@ -23,44 +35,79 @@ impl Projection {
         Self {
             center: (0.0, 0.0),
             studs_per_pixel: 100.0, // Default zoom
+            rotation: 0.0,
+            origin: Pos2::ZERO,
             screen_width,
             screen_height,
         }
     }
     
+    /// Rotate a vector by `degrees` clockwise as seen on screen (+y is down)
+    fn rotate(dx: f64, dy: f64, degrees: f64) -> (f64, f64) {
+        if degrees == 0.0 {
+            return (dx, dy);
+        }
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        (dx * cos - dy * sin, dx * sin + dy * cos)
+    }
+    
     /// Convert PTFS studs coordinates to screen pixels
     /// Note: In PTFS, -y is North, -x is West
     pub fn studs_to_screen(&self, studs_x: f64, studs_y: f64) -> Pos2 {
-        // Calculate offset from center
-        let dx = studs_x - self.center.0;
-        let dy = studs_y - self.center.1;
+        // Calculate offset from center, turning the rotation heading to the top
+        let (dx, dy) = Self::rotate(
+            studs_x - self.center.0,
+            studs_y - self.center.1,
+            -self.rotation,
+        );
         
         // Convert to screen space
         // In screen space: +x is right, +y is down
         // In PTFS: -x is West (left), -y is North (up)
-        let screen_x = self.screen_width / 2.0 + (dx / self.studs_per_pixel) as f32;
-        let screen_y = self.screen_height / 2.0 + (dy / self.studs_per_pixel) as f32;
+        let screen_x = self.origin.x + self.screen_width / 2.0 + (dx / self.studs_per_pixel) as f32;
+        let screen_y = self.origin.y + self.screen_height / 2.0 + (dy / self.studs_per_pixel) as f32;
         
         Pos2::new(screen_x, screen_y)
     }
     
     /// Convert screen pixels to PTFS studs coordinates
     pub fn screen_to_studs(&self, screen_pos: Pos2) -> (f64, f64) {
-        let dx = (screen_pos.x - self.screen_width / 2.0) as f64 * self.studs_per_pixel;
-        let dy = (screen_pos.y - self.screen_height / 2.0) as f64 * self.studs_per_pixel;
+        let (dx, dy) = self.screen_delta_to_studs(Vec2::new(
+            screen_pos.x - self.origin.x - self.screen_width / 2.0,
+            screen_pos.y - self.origin.y - self.screen_height / 2.0,
+        ));
         
         (self.center.0 + dx, self.center.1 + dy)
     }
     
+    /// Convert a screen-space offset to a studs offset
+    fn screen_delta_to_studs(&self, delta: Vec2) -> (f64, f64) {
+        Self::rotate(
+            delta.x as f64 * self.studs_per_pixel,
+            delta.y as f64 * self.studs_per_pixel,
+            self.rotation,
+        )
+    }
+    
+    /// Screen-space unit vector pointing along a true heading
+    pub fn heading_vector(&self, heading: f64) -> Vec2 {
+        let angle = (heading - self.rotation - 90.0).to_radians(); // -90 to align with North
+        Vec2::new(angle.cos() as f32, angle.sin() as f32)
+    }
+    
     /// Pan the view by screen pixels
     pub fn pan(&mut self, delta_screen: Vec2) {
-        let delta_studs_x = delta_screen.x as f64 * self.studs_per_pixel;
-        let delta_studs_y = delta_screen.y as f64 * self.studs_per_pixel;
+        let (delta_studs_x, delta_studs_y) = self.screen_delta_to_studs(delta_screen);
         
         self.center.0 -= delta_studs_x;
         self.center.1 -= delta_studs_y;
     }
     
+    /// Set the heading shown at the top of the scope, keeping it in 0-360
+    pub fn set_rotation(&mut self, degrees: f64) {
+        self.rotation = degrees.rem_euclid(360.0);
+    }
+    
     /// Zoom in/out (positive = zoom in, negative = zoom out)
     pub fn zoom(&mut self, delta: f32, mouse_pos: Option<Pos2>) {
         let zoom_factor = if delta > 0.0 { 0.9 } else { 1.1 };
@@ -82,119 +129,283 @@ impl Projection {
         self.studs_per_pixel = self.studs_per_pixel.clamp(1.0, 1000.0);
     }
     
-    /// Update screen dimensions
-    pub fn update_screen_size(&mut self, width: f32, height: f32) {
-        self.screen_width = width;
-        self.screen_height = height;
+    /// Update the area of the screen the scope is drawn in
+    pub fn update_screen_rect(&mut self, rect: Rect) {
+        self.origin = rect.min;
+        self.screen_width = rect.width();
+        self.screen_height = rect.height();
     }
 }
 
+/// Shared data drawn on a frame
+pub struct Scene<'a> {
+    /// Aircraft currently being tracked
+    pub aircraft: &'a HashMap<String, TrackedAircraft>,
+    
+    /// Per-aircraft tag offsets set by dragging
+    pub tag_offsets: &'a HashMap<String, (f32, f32)>,
+    
+    /// Callsigns tracked (assumed) by this controller
+    pub tracked_by_me: &'a HashSet<String>,
+    
+    /// Wake separation advisories keyed by follower callsign
+    pub wake_advisories: &'a HashMap<String, WakeAdvisory>,
+    
+    /// Controller annotations keyed by callsign
+    pub annotations: &'a HashMap<String, Annotation>,
+}
+
 /// Radar rendering engine
 pub struct RadarRenderer {
     /// Selected aircraft callsign
     pub selected_aircraft: Option<String>,
+    
+    /// Aircraft under the mouse pointer (its tag is expanded)
+    pub hovered_aircraft: Option<String>,
+    
+    /// Sidebar search filter, used by the `filter_match` tag mode
+    pub search_filter: String,
+    
+    /// Data tag placement engine (keeps state between frames)
+    label_placer: LabelPlacer,
+    
+    /// Tag rect and target position drawn last frame, for hit-testing
+    tag_rects: HashMap<String, (Rect, Pos2)>,
+    
+    /// Filter result for each aircraft this frame
+    visibility: HashMap<String, Visibility>,
+    
+    /// Opacity of dimmed aircraft this frame
+    dim_opacity: f32,
 }
 
 impl RadarRenderer {
     pub fn new() -> Self {
         Self {
             selected_aircraft: None,
+            hovered_aircraft: None,
+            search_filter: String::new(),
+            label_placer: LabelPlacer::new(),
+            tag_rects: HashMap::new(),
+            visibility: HashMap::new(),
+            dim_opacity: 1.0,
         }
     }
     
+    /// Find the aircraft whose data tag is under a screen position
+    pub fn tag_at(&self, pos: Pos2) -> Option<String> {
+        self.tag_rects
+            .iter()
+            .filter(|(_, (rect, _))| rect.contains(pos))
+            .min_by(|a, b| a.0.cmp(b.0))
+            .map(|(callsign, _)| callsign.clone())
+    }
+    
+    /// Current tag offset of an aircraft as drawn last frame
+    pub fn tag_offset(&self, callsign: &str) -> Option<(f32, f32)> {
+        self.tag_rects.get(callsign).map(|(rect, anchor)| {
+            let offset = rect.min - *anchor;
+            (offset.x, offset.y)
+        })
+    }
+    
+    /// Offset that moves an aircraft's tag to the next standard position clockwise
+    pub fn rotated_tag_offset(&self, callsign: &str, gap: f32) -> Option<(f32, f32)> {
+        self.tag_rects.get(callsign).map(|(rect, anchor)| {
+            let current = TagPosition::from_offset(rect.center() - *anchor);
+            let offset = current.next().offset(rect.size(), gap);
+            (offset.x, offset.y)
+        })
+    }
+    
     /// Render all aircraft on the radar
     pub fn render(
-        &self,
+        &mut self,
         painter: &egui::Painter,
         projection: &Projection,
-        aircraft: &HashMap<String, TrackedAircraft>,
-        display_config: &DisplayConfig,
-        color_config: &ColorConfig,
-        tag_config: &crate::config::DataTagConfig,
+        scene: &Scene,
+        config: &RadarConfig,
         time_millis: i64,
     ) {
+        let aircraft = scene.aircraft;
+        let display_config = &config.display;
+        let color_config = &config.colors;
+        
+        // Apply display filters (drawing only, alerting still sees everything)
+        let selected = self.selected_aircraft.as_deref();
+        self.visibility = aircraft
+            .values()
+            .map(|tracked| {
+                let is_selected = Some(tracked.callsign.as_str()) == selected;
+                (tracked.callsign.clone(), config.filters.visibility(tracked, is_selected))
+            })
+            .collect();
+        self.dim_opacity = config.filters.dim_opacity.clamp(0.0, 1.0);
+        
+        let shown: Vec<&TrackedAircraft> = aircraft
+            .values()
+            .filter(|tracked| self.visibility_of(&tracked.callsign) != Visibility::Hidden)
+            .collect();
+        
         // Render in layers for proper z-order
         
-        // 1. History trails
+        // 0. Compass rose
+        if display_config.show_compass_rose {
+            Self::render_compass_rose(painter, projection, display_config, color_config);
+        }
+        
+        // 1. Filter area outlines
+        let area_stroke = Stroke::new(1.0, color_config.color(&color_config.filter_area));
+        for area in config.filters.areas.iter().filter(|area| area.enabled) {
+            let points = area.points
+                .iter()
+                .map(|(x, y)| projection.studs_to_screen(*x, *y))
+                .collect();
+            painter.add(egui::Shape::closed_line(points, area_stroke));
+        }
+        
+        // 2. History trails
         if display_config.show_history {
-            for tracked in aircraft.values() {
+            for tracked in &shown {
                 self.render_history(painter, projection, tracked, display_config, color_config);
             }
         }
         
-        // 2. Predictive vectors
+        // 3. Predictive vectors
         if display_config.show_vectors {
-            for tracked in aircraft.values() {
+            for tracked in &shown {
                 self.render_vector(painter, projection, tracked, display_config, color_config);
             }
         }
         
-        // 3. Aircraft targets
-        for tracked in aircraft.values() {
-            self.render_target(painter, projection, tracked, display_config, color_config, time_millis);
+        // 4. Wake separation warnings, follower to leader
+        let wake_stroke = Stroke::new(1.5, color_config.color(&color_config.wake_alert));
+        for (callsign, advisory) in scene.wake_advisories.iter().filter(|(_, a)| a.infringed) {
+            if let (Some(follower), Some(leader)) = (aircraft.get(callsign), aircraft.get(&advisory.leader)) {
+                painter.line_segment(
+                    [
+                        projection.studs_to_screen(follower.info.position.x, follower.info.position.y),
+                        projection.studs_to_screen(leader.info.position.x, leader.info.position.y),
+                    ],
+                    wake_stroke,
+                );
+            }
         }
         
-        // 4. Data tags
+        // 5. Aircraft targets
+        for tracked in &shown {
+            self.render_target(painter, projection, tracked, config, time_millis);
+        }
+        
+        // 6. Data tags
         if display_config.show_tags {
-            for tracked in aircraft.values() {
-                self.render_data_tag(painter, projection, tracked, display_config, color_config, tag_config);
+            self.render_data_tags(painter, projection, scene, config);
+        } else {
+            self.tag_rects.clear();
+        }
+    }
+    
+    /// Draw bearing ticks around the scope, labelled in magnetic degrees
+    fn render_compass_rose(
+        painter: &egui::Painter,
+        projection: &Projection,
+        display_config: &DisplayConfig,
+        color_config: &ColorConfig,
+    ) {
+        let color = color_config.color(&color_config.compass);
+        let stroke = Stroke::new(1.0, color);
+        let font = egui::FontId::monospace(display_config.font_size * 0.9);
+        let center = projection.studs_to_screen(projection.center.0, projection.center.1);
+        let radius = projection.screen_width.min(projection.screen_height) / 2.0 - 12.0;
+        
+        if radius <= 40.0 {
+            return;
+        }
+        
+        painter.circle_stroke(center, radius, stroke);
+        
+        for bearing in (0..360).step_by(10) {
+            let true_bearing = bearing as f64 + display_config.magnetic_variation;
+            let direction = projection.heading_vector(true_bearing);
+            let tick = if bearing % 30 == 0 { 10.0 } else { 5.0 };
+            
+            painter.line_segment(
+                [center + direction * radius, center + direction * (radius - tick)],
+                stroke,
+            );
+            
+            if bearing % 30 == 0 {
+                // Labelled like runway headings, north is 36
+                let label = if bearing == 0 { 36 } else { bearing / 10 };
+                painter.text(
+                    center + direction * (radius - tick - 10.0),
+                    egui::Align2::CENTER_CENTER,
+                    format!("{:02}", label),
+                    font.clone(),
+                    color,
+                );
             }
         }
     }
     
+    /// Filter result for an aircraft this frame
+    pub fn visibility_of(&self, callsign: &str) -> Visibility {
+        self.visibility.get(callsign).copied().unwrap_or(Visibility::Shown)
+    }
+    
+    /// Fade a color for dimmed aircraft
+    fn faded(&self, callsign: &str, color: Color32) -> Color32 {
+        match self.visibility_of(callsign) {
+            Visibility::Dimmed => color.gamma_multiply(self.dim_opacity),
+            _ => color,
+        }
+    }
+    
     /// Render aircraft target symbol (diamond/square)
     fn render_target(
         &self,
         painter: &egui::Painter,
         projection: &Projection,
         tracked: &TrackedAircraft,
-        display: &DisplayConfig,
-        colors: &ColorConfig,
+        config: &RadarConfig,
         time_millis: i64,
     ) {
+        let display = &config.display;
+        let colors = &config.colors;
+        let style = style_for(&config.target_rules, tracked);
+        
         let pos = projection.studs_to_screen(
             tracked.info.position.x,
             tracked.info.position.y,
         );
         
-        // Determine color
+        // Determine color (emergency and selection override rule colors)
         let color = if tracked.info.is_emergency_occuring {
             // Flash emergency aircraft
             let flash = (time_millis / 500) % 2 == 0;
             if flash {
-                parse_color(&colors.target_emergency)
+                colors.color(&colors.target_emergency)
             } else {
                 Color32::TRANSPARENT
             }
         } else if Some(&tracked.callsign) == self.selected_aircraft.as_ref() {
-            parse_color(&colors.target_selected)
+            colors.color(&colors.target_selected)
+        } else if let Some(rule_color) = style.color {
+            colors.color(rule_color)
         } else if tracked.info.is_on_ground.unwrap_or(false) {
-            parse_color(&colors.ground)
+            colors.color(&colors.ground)
         } else {
-            parse_color(&colors.target)
+            colors.color(&colors.target)
         };
+        let color = self.faded(&tracked.callsign, color);
         
-        // Draw target symbol (diamond shape)
-        let size = 6.0 * display.target_scale;
-        let points = vec![
-            pos + Vec2::new(0.0, -size),      // Top
-            pos + Vec2::new(size, 0.0),       // Right
-            pos + Vec2::new(0.0, size),       // Bottom
-            pos + Vec2::new(-size, 0.0),      // Left
-        ];
-        
-        painter.add(egui::Shape::closed_line(
-            points,
-            Stroke::new(display.target_stroke, color),
-        ));
+        // Draw target symbol
+        let size = 6.0 * display.target_scale * style.size;
+        Self::draw_symbol(painter, pos, size, style.symbol, Stroke::new(display.target_stroke, color));
         
         // Draw heading indicator
-        let heading_rad = (tracked.info.heading - 90.0).to_radians(); // -90 to align with North
         let heading_len = size * 2.0;
-        let heading_end = pos + Vec2::new(
-            (heading_rad.cos() * heading_len as f64) as f32,
-            (heading_rad.sin() * heading_len as f64) as f32,
-        );
+        let heading_end = pos + projection.heading_vector(tracked.info.heading) * heading_len;
         
         painter.line_segment(
             [pos, heading_end],
@@ -202,7 +413,51 @@ impl RadarRenderer {
         );
     }
     
-    /// Render history trail dots
+    /// Draw a target symbol centred on `pos`
+    fn draw_symbol(painter: &egui::Painter, pos: Pos2, size: f32, symbol: TargetSymbol, stroke: Stroke) {
+        match symbol {
+            TargetSymbol::Diamond => {
+                let points = vec![
+                    pos + Vec2::new(0.0, -size),      // Top
+                    pos + Vec2::new(size, 0.0),       // Right
+                    pos + Vec2::new(0.0, size),       // Bottom
+                    pos + Vec2::new(-size, 0.0),      // Left
+                ];
+                painter.add(egui::Shape::closed_line(points, stroke));
+            }
+            TargetSymbol::Square => {
+                let half = size * 0.75;
+                painter.rect_stroke(Rect::from_center_size(pos, Vec2::splat(half * 2.0)), 0.0, stroke);
+            }
+            TargetSymbol::Circle => {
+                painter.circle_stroke(pos, size * 0.8, stroke);
+            }
+            TargetSymbol::Triangle => {
+                let points = vec![
+                    pos + Vec2::new(0.0, -size),
+                    pos + Vec2::new(size * 0.87, size * 0.5),
+                    pos + Vec2::new(-size * 0.87, size * 0.5),
+                ];
+                painter.add(egui::Shape::closed_line(points, stroke));
+            }
+            TargetSymbol::Hexagon => {
+                let points = (0..6)
+                    .map(|i| {
+                        let angle = (i as f32 * 60.0).to_radians();
+                        pos + Vec2::new(angle.cos(), angle.sin()) * size * 0.85
+                    })
+                    .collect();
+                painter.add(egui::Shape::closed_line(points, stroke));
+            }
+            TargetSymbol::Cross => {
+                let d = size * 0.7;
+                painter.line_segment([pos + Vec2::new(-d, -d), pos + Vec2::new(d, d)], stroke);
+                painter.line_segment([pos + Vec2::new(-d, d), pos + Vec2::new(d, -d)], stroke);
+            }
+        }
+    }
+    
+    /// Render history trail dots, fading older ones
     fn render_history(
         &self,
         painter: &egui::Painter,
@@ -211,10 +466,24 @@ impl RadarRenderer {
         display: &DisplayConfig,
         colors: &ColorConfig,
     ) {
-        let color = parse_color(&colors.history);
+        let color = self.faded(&tracked.callsign, colors.color(&colors.history));
         let dot_size = display.history_dot_size;
+        let now = chrono::Utc::now().timestamp_millis();
+        let duration_ms = (display.history_duration_secs * 1000.0).max(1.0);
         
-        for (x, y, _timestamp) in &tracked.history {
+        for (x, y, timestamp) in &tracked.history {
+            // Stale aircraft stop trimming their trail, so skip expired dots here too
+            let age = (now - timestamp) as f32;
+            if age > duration_ms {
+                continue;
+            }
+            
+            let color = if display.history_fade {
+                color.gamma_multiply(1.0 - age / duration_ms)
+            } else {
+                color
+            };
+            
             let pos = projection.studs_to_screen(*x, *y);
             painter.circle_filled(pos, dot_size, color);
         }
@@ -236,13 +505,13 @@ impl RadarRenderer {
         
         // Calculate predicted position based on ground speed and heading
         // PTFS uses 1 knot = 0.5442765 studs/sec
-        let studs_per_knot_per_sec = 0.5442765;
+        let studs_per_knot_per_sec: f64 = 0.5442765;
         let seconds_ahead = display.vector_minutes * 60.0;
         
         let gs_knots = tracked.info.ground_speed;
         let heading_rad = (tracked.info.heading - 90.0).to_radians();
         
-        let distance_studs = (gs_knots * studs_per_knot_per_sec as f64 * seconds_ahead as f64) as f64;
+        let distance_studs = gs_knots * studs_per_knot_per_sec * seconds_ahead as f64;
         
         let predicted_x = tracked.info.position.x + distance_studs * heading_rad.cos();
         let predicted_y = tracked.info.position.y + distance_studs * heading_rad.sin();
@@ -251,89 +520,162 @@ impl RadarRenderer {
         
         painter.line_segment(
             [current_pos, predicted_pos],
-            Stroke::new(1.5, parse_color(&colors.vector)),
+            Stroke::new(1.5, self.faded(&tracked.callsign, colors.color(&colors.vector))),
         );
     }
     
-    /// Render aircraft data tag
-    fn render_data_tag(
-        &self,
+    /// Render all data tags, placing them to minimise overlap
+    ///
+    /// Tags with a per-aircraft offset (dragged by the controller) stay where
+    /// they were put; the rest are placed around them.
+    fn render_data_tags(
+        &mut self,
         painter: &egui::Painter,
         projection: &Projection,
-        tracked: &TrackedAircraft,
-        display: &DisplayConfig,
-        colors: &ColorConfig,
-        tag_config: &crate::config::DataTagConfig,
+        scene: &Scene,
+        config: &RadarConfig,
     ) {
-        let pos = projection.studs_to_screen(
-            tracked.info.position.x,
-            tracked.info.position.y,
-        );
+        let aircraft = scene.aircraft;
+        let tag_offsets = scene.tag_offsets;
+        let tag_config = &config.data_tags;
+        let text_color = config.colors.color(&config.colors.tag_text);
+        let font = egui::FontId::monospace(config.display.font_size);
+        let screen = painter.clip_rect();
+        let filter = self.search_filter.to_lowercase();
+        
+        // Templates are parsed once per frame for each layout in use
+        let mut layouts: HashMap<Option<&str>, Vec<Template>> = HashMap::new();
         
-        // Apply offset from config
-        let tag_pos = pos + Vec2::new(tag_config.offset.0, tag_config.offset.1);
+        // Lay out every tag first so placement knows its size
+        let mut tags = Vec::with_capacity(aircraft.len());
+        for tracked in aircraft.values() {
+            let anchor = projection.studs_to_screen(
+                tracked.info.position.x,
+                tracked.info.position.y,
+            );
+            
+            // Skip filtered targets and those well outside the view
+            if
+                self.visibility_of(&tracked.callsign) == Visibility::Hidden ||
+                !screen.expand(200.0).contains(anchor)
+            {
+                continue;
+            }
+            
+            let callsign = Some(tracked.callsign.as_str());
+            let matches = |condition| match condition {
+                TagCondition::Selected => callsign == self.selected_aircraft.as_deref(),
+                TagCondition::OnGround => tracked.info.is_on_ground.unwrap_or(false),
+                TagCondition::Emergency => tracked.info.is_emergency_occuring,
+                TagCondition::FilterMatch => {
+                    !filter.is_empty() && tracked.callsign.to_lowercase().contains(&filter)
+                }
+                TagCondition::Tracked => scene.tracked_by_me.contains(&tracked.callsign),
+            };
+            let hovered = callsign == self.hovered_aircraft.as_deref();
+            let layout = tag_config.layout_for(matches, hovered);
+            let templates = layouts
+                .entry(layout)
+                .or_insert_with(|| tag_config.parsed_layout(layout));
+            
+            let ctx = TagContext {
+                tracked,
+                wake: scene.wake_advisories.get(&tracked.callsign),
+                annotation: scene.annotations.get(&tracked.callsign),
+                wake_mode: config.wake.mode,
+                magnetic_variation: config.display.magnetic_variation,
+            };
+            let color = self.faded(&tracked.callsign, text_color);
+            let galleys: Vec<_> = Self::build_tag_lines(&ctx, templates)
+                .into_iter()
+                .map(|line| painter.layout_no_wrap(line, font.clone(), color))
+                .collect();
+            
+            let width = galleys.iter().map(|g| g.size().x).fold(0.0, f32::max);
+            let height = (galleys.len().saturating_sub(1)) as f32 * tag_config.line_spacing
+                + galleys.last().map_or(0.0, |g| g.size().y);
+            
+            tags.push((tracked, anchor, galleys, Vec2::new(width, height)));
+        }
         
-        let text_color = parse_color(&colors.tag_text);
+        // Selected aircraft gets first pick, the rest in a stable order
+        let selected = self.selected_aircraft.as_deref();
+        tags.sort_by(|a, b| {
+            (Some(b.0.callsign.as_str()) == selected)
+                .cmp(&(Some(a.0.callsign.as_str()) == selected))
+                .then_with(|| a.0.callsign.cmp(&b.0.callsign))
+        });
         
-        // Build lines from template config
-        let mut lines = Vec::new();
-        lines.push(Self::format_tag_line(&tag_config.line1, tracked));
-        lines.push(Self::format_tag_line(&tag_config.line2, tracked));
+        // Manually positioned tags, with a leader line once dragged clear of the target
+        let mut rects: HashMap<String, (Rect, bool)> = tags
+            .iter()
+            .filter_map(|(tracked, anchor, _, size)| {
+                let (x, y) = tag_offsets.get(&tracked.callsign)?;
+                let rect = Rect::from_min_size(*anchor + Vec2::new(*x, *y), *size);
+                let leader = rect.distance_to_pos(*anchor) > tag_config.placement_gap * 1.5;
+                Some((tracked.callsign.clone(), (rect, leader)))
+            })
+            .collect();
         
-        if let Some(line3) = &tag_config.line3 {
-            lines.push(Self::format_tag_line(line3, tracked));
+        if tag_config.auto_placement {
+            let fixed: Vec<Rect> = rects.values().map(|(rect, _)| *rect).collect();
+            let requests: Vec<LabelRequest> = tags
+                .iter()
+                .filter(|(tracked, _, _, _)| !rects.contains_key(&tracked.callsign))
+                .map(|(tracked, anchor, _, size)| LabelRequest {
+                    callsign: tracked.callsign.clone(),
+                    anchor: *anchor,
+                    size: *size,
+                })
+                .collect();
+            let targets: Vec<Pos2> = tags.iter().map(|(_, anchor, _, _)| *anchor).collect();
+            let target_size = 6.0 * config.display.target_scale;
+            
+            let placed = self.label_placer
+                .place(&requests, &fixed, &targets, target_size, screen, tag_config);
+            rects.extend(
+                placed
+                    .into_iter()
+                    .map(|(callsign, placed)| (callsign, (placed.rect, placed.leader))),
+            );
+        } else {
+            let offset = Vec2::new(tag_config.offset.0, tag_config.offset.1);
+            for (tracked, anchor, _, size) in &tags {
+                rects
+                    .entry(tracked.callsign.clone())
+                    .or_insert((Rect::from_min_size(*anchor + offset, *size), false));
+            }
         }
         
-        if let Some(line4) = &tag_config.line4 {
-            lines.push(Self::format_tag_line(line4, tracked));
-        }
+        self.tag_rects.clear();
         
-        // Render lines
-        for (i, line) in lines.iter().enumerate() {
-            let line_pos = tag_pos + Vec2::new(0.0, i as f32 * tag_config.line_spacing);
+        let leader_stroke = Stroke::new(1.0, config.colors.color(&config.colors.leader_line));
+        
+        for (tracked, anchor, galleys, _) in tags {
+            let Some((rect, leader)) = rects.get(&tracked.callsign) else {
+                continue;
+            };
             
-            painter.text(
-                line_pos,
-                egui::Align2::LEFT_TOP,
-                line,
-                egui::FontId::monospace(display.font_size),
-                text_color,
-            );
+            self.tag_rects.insert(tracked.callsign.clone(), (*rect, anchor));
+            
+            if *leader && tag_config.leader_lines {
+                painter.line_segment([anchor, leader_end(rect, anchor)], leader_stroke);
+            }
+            
+            for (i, galley) in galleys.into_iter().enumerate() {
+                let line_pos = rect.min + Vec2::new(0.0, i as f32 * tag_config.line_spacing);
+                painter.galley(line_pos, galley, text_color);
+            }
         }
     }
     
-    /// Format data tag using template system
-    fn format_data_tag(&self, tracked: &TrackedAircraft, _display: &DisplayConfig) -> Vec<String> {
-        // We'll need to add this to RadarRenderer to get the full config
-        // For now, use default formatting
-        let altitude = (tracked.info.altitude / 100.0) as i32;
-        let gs = tracked.info.ground_speed as i32;
-        
-        vec![
-            tracked.callsign.clone(),
-            format!("F{:03} {:03}KT", altitude, gs),
-        ]
-    }
-    
-    /// Format data tag line using template string
-    /// Supports variables: {callsign}, {altitude}, {speed}, {gs}, {heading}, {type}
-    pub fn format_tag_line(template: &str, tracked: &TrackedAircraft) -> String {
-        let altitude = (tracked.info.altitude / 100.0) as i32;
-        let speed = tracked.info.speed as i32;
-        let gs = tracked.info.ground_speed as i32;
-        let heading = tracked.info.heading as i32;
-        
-        template
-            .replace("{callsign}", &tracked.callsign)
-            .replace("{altitude:03}", &format!("{:03}", altitude))
-            .replace("{altitude}", &altitude.to_string())
-            .replace("{speed:03}", &format!("{:03}", speed))
-            .replace("{speed}", &speed.to_string())
-            .replace("{gs:03}", &format!("{:03}", gs))
-            .replace("{gs}", &gs.to_string())
-            .replace("{heading:03}", &format!("{:03}", heading))
-            .replace("{heading}", &heading.to_string())
-            .replace("{type}", &tracked.info.aircraft_type)
+    /// Build the text lines of a tag, dropping lines that render empty
+    fn build_tag_lines(ctx: &TagContext, templates: &[Template]) -> Vec<String> {
+        templates
+            .iter()
+            .map(|template| template.render(ctx))
+            .filter(|line| !line.trim().is_empty())
+            .collect()
     }
 }
 
@@ -343,16 +685,64 @@ impl Default for RadarRenderer {
     }
 }
 
-/// Parse hex color string to Color32
-pub fn parse_color(hex: &str) -> Color32 {
-    let hex = hex.trim_start_matches('#');
-    
-    if hex.len() == 6 {
-        let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(0);
-        let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(0);
-        let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(0);
-        Color32::from_rgb(r, g, b)
+/// Convert a true bearing to magnetic (variation east positive), in 0-360
+pub fn magnetic_bearing(true_bearing: f64, variation: f64) -> f64 {
+    (true_bearing - variation).rem_euclid(360.0)
+}
+
+/// Colors that can be given by name instead of hex
+pub const NAMED_COLORS: &[(&str, Color32)] = &[
+    ("black", Color32::from_rgb(0, 0, 0)),
+    ("white", Color32::from_rgb(255, 255, 255)),
+    ("gray", Color32::from_rgb(128, 128, 128)),
+    ("grey", Color32::from_rgb(128, 128, 128)),
+    ("lightgray", Color32::from_rgb(192, 192, 192)),
+    ("lightgrey", Color32::from_rgb(192, 192, 192)),
+    ("darkgray", Color32::from_rgb(64, 64, 64)),
+    ("darkgrey", Color32::from_rgb(64, 64, 64)),
+    ("red", Color32::from_rgb(255, 0, 0)),
+    ("maroon", Color32::from_rgb(128, 0, 0)),
+    ("orange", Color32::from_rgb(255, 136, 0)),
+    ("amber", Color32::from_rgb(255, 191, 0)),
+    ("gold", Color32::from_rgb(255, 215, 0)),
+    ("yellow", Color32::from_rgb(255, 255, 0)),
+    ("lime", Color32::from_rgb(0, 255, 0)),
+    ("green", Color32::from_rgb(0, 170, 0)),
+    ("olive", Color32::from_rgb(128, 128, 0)),
+    ("teal", Color32::from_rgb(0, 128, 128)),
+    ("cyan", Color32::from_rgb(0, 255, 255)),
+    ("blue", Color32::from_rgb(0, 136, 255)),
+    ("navy", Color32::from_rgb(0, 0, 128)),
+    ("purple", Color32::from_rgb(128, 0, 128)),
+    ("magenta", Color32::from_rgb(255, 0, 255)),
+    ("pink", Color32::from_rgb(255, 150, 200)),
+    ("transparent", Color32::TRANSPARENT),
+];
+
+/// Parse a color: "#RRGGBB", "#RRGGBBAA" with alpha, or a name such as "amber"
+/// The leading '#' is optional. Palette references are resolved by `ColorConfig`.
+pub fn try_parse_color(value: &str) -> Option<Color32> {
+    let value = value.trim();
+    if let Some((_, color)) = NAMED_COLORS.iter().find(|(name, _)| name.eq_ignore_ascii_case(value)) {
+        return Some(*color);
+    }
+    
+    let hex = value.trim_start_matches('#');
+    if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some(Color32::from_rgba_unmultiplied(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
+
+/// Format a color as hex, with alpha only when it is see-through
+pub fn color_to_hex(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == 255 {
+        format!("#{:02X}{:02X}{:02X}", r, g, b)
     } else {
-        Color32::WHITE
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
     }
 }
\ No newline at end of file
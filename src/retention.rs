@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How long strips and other per-aircraft data outlive the aircraft leaving the feed
+pub const OFF_FEED_RETENTION_MS: i64 = 30 * 60 * 1000;
+
+/// How often saved last-seen times are brought up to date while aircraft stay on the feed
+pub const LAST_SEEN_SAVE_INTERVAL_MS: i64 = 60 * 1000;
+
+/// Entry saved with when its aircraft was last on the feed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Kept<T> {
+    #[serde(flatten)]
+    pub value: T,
+
+    /// Unix time in milliseconds
+    /// Files from before this was recorded count from when they are loaded.
+    #[serde(default = "now_millis")]
+    pub last_seen: i64,
+}
+
+pub fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Whether an aircraft last seen at `last_seen` has been off the feed too long
+pub fn expired(last_seen: i64, now: i64) -> bool {
+    now - last_seen > OFF_FEED_RETENTION_MS
+}
+
+/// Drop entries of aircraft no longer in `last_seen`, returning whether any were dropped
+pub fn retain_seen<T>(entries: &mut HashMap<String, T>, last_seen: &HashMap<String, i64>) -> bool {
+    let count = entries.len();
+    entries.retain(|callsign, _| last_seen.contains_key(callsign));
+    entries.len() != count
+}
+
+/// Pair entries with when their aircraft was last seen, for saving
+/// Entries for aircraft not seen yet count as seen now.
+pub fn with_last_seen<T: Clone>(entries: &HashMap<String, T>, last_seen: &HashMap<String, i64>) -> Vec<(String, Kept<T>)> {
+    let now = now_millis();
+    let mut kept: Vec<(String, Kept<T>)> = entries
+        .iter()
+        .map(|(callsign, value)| {
+            let last_seen = last_seen.get(callsign).copied().unwrap_or(now);
+            (callsign.clone(), Kept { value: value.clone(), last_seen })
+        })
+        .collect();
+    kept.sort_by(|(a, _), (b, _)| a.cmp(b));
+    kept
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+
+use crate::aircraft_db::{AircraftCategory, WakeCategory};
+use crate::types::{FlightPhase, TrackedAircraft};
+use crate::validation::Validator;
+
+/// Shape drawn for a target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetSymbol {
+    #[default]
+    Diamond,
+    Square,
+    Circle,
+    Triangle,
+    Cross,
+    Hexagon,
+}
+
+impl TargetSymbol {
+    /// Default symbol for an aircraft category
+    pub fn for_category(category: AircraftCategory) -> Self {
+        match category {
+            AircraftCategory::Heavy => TargetSymbol::Square,
+            AircraftCategory::Jet | AircraftCategory::Unknown => TargetSymbol::Diamond,
+            AircraftCategory::Turboprop => TargetSymbol::Hexagon,
+            AircraftCategory::Light => TargetSymbol::Circle,
+            AircraftCategory::Helicopter => TargetSymbol::Cross,
+            AircraftCategory::Military => TargetSymbol::Triangle,
+        }
+    }
+}
+
+/// User-defined presentation rule for targets
+///
+/// All conditions in `when` must hold for the rule to match; the first
+/// matching rule in the list wins. Emergency flashing and the selection
+/// colour still take priority over a rule's colour.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TargetRule {
+    /// Optional name, for your own reference
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Conditions to match
+    #[serde(default)]
+    pub when: TargetMatch,
+
+    /// Target color (hex), unset = normal target/ground color
+    #[serde(default)]
+    pub color: Option<String>,
+
+    /// Symbol shape, unset = the aircraft category's symbol
+    #[serde(default)]
+    pub symbol: Option<TargetSymbol>,
+
+    /// Size multiplier on top of display.target_scale, unset = 1.0
+    #[serde(default)]
+    pub size: Option<f32>,
+}
+
+/// Conditions a target rule matches on; unset fields match anything
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TargetMatch {
+    /// Aircraft type contains this text (case-insensitive), e.g. "A380"
+    #[serde(default)]
+    pub aircraft_type: Option<String>,
+
+    /// Minimum altitude in feet
+    #[serde(default)]
+    pub altitude_min: Option<f64>,
+
+    /// Maximum altitude in feet
+    #[serde(default)]
+    pub altitude_max: Option<f64>,
+
+    /// Flight rules from the flight plan ("IFR" or "VFR")
+    #[serde(default)]
+    pub flight_rules: Option<String>,
+
+    /// Departure airport from the flight plan
+    #[serde(default)]
+    pub departure: Option<String>,
+
+    /// Arrival airport from the flight plan
+    #[serde(default)]
+    pub arrival: Option<String>,
+
+    /// Aircraft comes from the event server
+    #[serde(default)]
+    pub event_server: Option<bool>,
+
+    /// Flight phase: ground, climbing, descending or level
+    #[serde(default)]
+    pub phase: Option<FlightPhase>,
+
+    /// Callsign pattern, `*` matches any run of characters and `?` one character
+    #[serde(default)]
+    pub callsign: Option<String>,
+
+    /// Aircraft category from the type database
+    #[serde(default)]
+    pub category: Option<AircraftCategory>,
+
+    /// Wake turbulence category ("L", "M", "H" or "J")
+    #[serde(default)]
+    pub wake: Option<WakeCategory>,
+}
+
+impl TargetRule {
+    pub fn validate(&self, v: &mut Validator) {
+        if let Some(color) = &self.color {
+            v.color("color", color);
+        }
+        if let Some(size) = self.size {
+            v.range("size", size, 0.1..=10.0);
+        }
+        if let (Some(min), Some(max)) = (self.when.altitude_min, self.when.altitude_max) && min > max {
+            v.nested("when", |v| v.error("altitude_min", "is above altitude_max"));
+        }
+    }
+}
+
+impl TargetMatch {
+    pub fn matches(&self, tracked: &TrackedAircraft) -> bool {
+        let info = &tracked.info;
+        let plan = tracked.flight_plan.as_ref();
+        let plan_is = |wanted: &Option<String>, field: Option<&String>| {
+            wanted
+                .as_ref()
+                .is_none_or(|w| field.is_some_and(|f| f.eq_ignore_ascii_case(w)))
+        };
+
+        self.aircraft_type.as_ref().is_none_or(|t| {
+            info.aircraft_type.to_lowercase().contains(&t.to_lowercase())
+        })
+            && self.altitude_min.is_none_or(|min| info.altitude >= min)
+            && self.altitude_max.is_none_or(|max| info.altitude <= max)
+            && plan_is(&self.flight_rules, plan.map(|fp| &fp.flight_rules))
+            && plan_is(&self.departure, plan.map(|fp| &fp.departing))
+            && plan_is(&self.arrival, plan.map(|fp| &fp.arriving))
+            && self.event_server.is_none_or(|event| tracked.is_event == event)
+            && self.phase.is_none_or(|phase| tracked.phase() == phase)
+            && self.callsign.as_ref().is_none_or(|p| wildcard_match(p, &tracked.callsign))
+            && self.category.is_none_or(|c| tracked.type_info.category == c)
+            && self.wake.is_none_or(|w| tracked.type_info.wake == w)
+    }
+}
+
+/// Resolved presentation of a target
+#[derive(Debug, Clone, Copy)]
+pub struct TargetStyle<'a> {
+    pub color: Option<&'a str>,
+    pub symbol: TargetSymbol,
+    pub size: f32,
+}
+
+/// Style from the first matching rule, or the defaults if none match
+pub fn style_for<'a>(rules: &'a [TargetRule], tracked: &TrackedAircraft) -> TargetStyle<'a> {
+    let rule = rules.iter().find(|rule| rule.when.matches(tracked));
+
+    TargetStyle {
+        color: rule.and_then(|r| r.color.as_deref()),
+        symbol: rule
+            .and_then(|r| r.symbol)
+            .unwrap_or_else(|| TargetSymbol::for_category(tracked.type_info.category)),
+        size: rule.and_then(|r| r.size).unwrap_or(1.0),
+    }
+}
+
+/// Case-insensitive glob match supporting `*` and `?`
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_uppercase().chars().collect();
+    let text: Vec<char> = text.to_uppercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
@@ -0,0 +1,314 @@
+use anyhow::{Context, Result};
+use serde::de::value::Error;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::path::Path;
+
+use crate::config::RadarConfig;
+
+/// File the schema is written to, next to config.toml
+pub const SCHEMA_FILE: &str = "config.schema.json";
+
+/// JSON Schema of config.toml, for editor autocomplete and checking
+///
+/// The shape comes from the config types themselves, by deserializing them
+/// from a deserializer that records what each field asks for. Defaults are
+/// filled in from `RadarConfig::default()`.
+pub fn config_schema() -> Value {
+    let mut schema = json!({});
+    if let Err(e) = RadarConfig::deserialize(Tracer { schema: &mut schema }) {
+        tracing::warn!("Config schema is incomplete: {}", e);
+    }
+
+    // Round-trip through text so f32 defaults read as written, e.g. 0.4 rather than 0.4000000059604645
+    let defaults = serde_json
+        ::to_string(&RadarConfig::default())
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or(Value::Null);
+    add_defaults(&mut schema, &defaults);
+
+    // Profiles override any part of the config, optionally building on another profile
+    schema["properties"]["profile"] = json!({
+        "type": "object",
+        "additionalProperties": {
+            "allOf": [{ "$ref": "#" }],
+            "properties": { "inherits": { "type": "string" } }
+        }
+    });
+
+    schema["$schema"] = json!("http://json-schema.org/draft-07/schema#");
+    schema["title"] = json!("FeritScope config");
+    schema
+}
+
+/// Write the schema next to the config file, if it changed
+pub fn write_schema(path: &Path) -> Result<()> {
+    let contents = serde_json::to_string_pretty(&config_schema())
+        .context("Failed to serialize config schema")?;
+
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Add default values, and the entries of maps whose keys are fixed by the defaults
+fn add_defaults(schema: &mut Value, default: &Value) {
+    match default {
+        Value::Null => {}
+        Value::Object(defaults) => {
+            for (key, default) in defaults {
+                let properties = &mut schema["properties"];
+                if properties.get(key).is_none() {
+                    // Map entry, such as a keybinding or a tag layout
+                    let entry = match schema.get("additionalProperties") {
+                        Some(entry) if entry.get("type").is_some() => entry.clone(),
+                        _ => type_of(default),
+                    };
+                    schema["properties"][key] = entry;
+                }
+                add_defaults(&mut schema["properties"][key], default);
+            }
+        }
+        default => schema["default"] = default.clone(),
+    }
+}
+
+/// Schema of the type of a JSON value, for entries only seen in defaults
+fn type_of(value: &Value) -> Value {
+    match value {
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(n) if n.is_f64() => json!({ "type": "number" }),
+        Value::Number(_) => json!({ "type": "integer" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(_) => json!({ "type": "array" }),
+        Value::Object(_) => json!({ "type": "object" }),
+        Value::Null => json!({}),
+    }
+}
+
+/// Deserializer that records the shape asked for instead of reading data
+///
+/// Every request is answered with a placeholder value so deserialization
+/// walks the whole type. Collections get one element to learn their item
+/// type; when a placeholder is rejected, such as a map key that must be a
+/// known name, the collection is left empty instead.
+struct Tracer<'a> {
+    schema: &'a mut Value,
+}
+
+macro_rules! trace_scalar {
+    ($($method:ident => $schema:expr, $visit:ident($($value:expr)?);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                *self.schema = $schema;
+                visitor.$visit($($value)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Tracer<'_> {
+    type Error = Error;
+
+    trace_scalar! {
+        deserialize_bool => json!({ "type": "boolean" }), visit_bool(false);
+        deserialize_i8 => json!({ "type": "integer" }), visit_i64(0);
+        deserialize_i16 => json!({ "type": "integer" }), visit_i64(0);
+        deserialize_i32 => json!({ "type": "integer" }), visit_i64(0);
+        deserialize_i64 => json!({ "type": "integer" }), visit_i64(0);
+        deserialize_u8 => json!({ "type": "integer", "minimum": 0 }), visit_u64(0);
+        deserialize_u16 => json!({ "type": "integer", "minimum": 0 }), visit_u64(0);
+        deserialize_u32 => json!({ "type": "integer", "minimum": 0 }), visit_u64(0);
+        deserialize_u64 => json!({ "type": "integer", "minimum": 0 }), visit_u64(0);
+        deserialize_f32 => json!({ "type": "number" }), visit_f64(0.0);
+        deserialize_f64 => json!({ "type": "number" }), visit_f64(0.0);
+        deserialize_char => json!({ "type": "string", "maxLength": 1 }), visit_char(' ');
+        deserialize_str => json!({ "type": "string" }), visit_str("");
+        deserialize_string => json!({ "type": "string" }), visit_str("");
+        deserialize_bytes => json!({ "type": "string" }), visit_bytes(&[]);
+        deserialize_byte_buf => json!({ "type": "string" }), visit_bytes(&[]);
+        deserialize_identifier => json!({ "type": "string" }), visit_str("");
+        deserialize_unit => json!({ "type": "null" }), visit_unit();
+        deserialize_ignored_any => json!({}), visit_unit();
+    }
+
+    /// Self-describing values such as `toml::Table` accept anything
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        *self.schema = json!({});
+        visitor.visit_map(Entries { slots: VecDeque::new() })
+    }
+
+    /// Absent in TOML rather than null, so only the inner type matters
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        *self.schema = json!({ "type": "array", "items": {} });
+        let items = &mut self.schema["items"];
+        visitor.visit_seq(Elements { slots: VecDeque::from([items]), lenient: true })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        *self.schema = json!({
+            "type": "array",
+            "items": vec![json!({}); len],
+            "minItems": len,
+            "maxItems": len,
+        });
+        let slots = self.schema["items"].as_array_mut().map(|items| items.iter_mut().collect()).unwrap_or_default();
+        visitor.visit_seq(Elements { slots, lenient: false })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        *self.schema = json!({ "type": "object", "additionalProperties": {} });
+        let entry = &mut self.schema["additionalProperties"];
+        visitor.visit_map(Entries { slots: VecDeque::from([(String::new(), entry)]) })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V
+    ) -> Result<V::Value, Error> {
+        let properties = fields
+            .iter()
+            .map(|field| (field.to_string(), json!({})))
+            .collect();
+        *self.schema = json!({ "type": "object", "properties": Value::Object(properties) });
+
+        let slots = match self.schema["properties"].as_object_mut() {
+            Some(properties) => properties
+                .iter_mut()
+                .map(|(field, schema)| (field.clone(), schema))
+                .collect(),
+            None => VecDeque::new(),
+        };
+        visitor.visit_map(Entries { slots })
+    }
+
+    /// Unit variants, written as their name
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V
+    ) -> Result<V::Value, Error> {
+        *self.schema = json!({ "type": "string", "enum": variants });
+        let variant = variants.first().copied().unwrap_or_default();
+        visitor.visit_enum(Variant { name: variant })
+    }
+}
+
+/// Array elements, each recording into its slot
+struct Elements<'a> {
+    slots: VecDeque<&'a mut Value>,
+
+    /// End the array early when an element rejects its placeholder
+    lenient: bool,
+}
+
+impl<'de> de::SeqAccess<'de> for Elements<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        let Some(schema) = self.slots.pop_front() else {
+            return Ok(None);
+        };
+        match seed.deserialize(Tracer { schema }) {
+            Ok(value) => Ok(Some(value)),
+            Err(_) if self.lenient => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Struct fields or a map entry, each value recording into its slot
+struct Entries<'a> {
+    slots: VecDeque<(String, &'a mut Value)>,
+}
+
+impl<'de> de::MapAccess<'de> for Entries<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        let Some((key, _)) = self.slots.front() else {
+            return Ok(None);
+        };
+
+        // A map whose keys must be known names stays empty
+        let key: de::value::StringDeserializer<Error> = key.clone().into_deserializer();
+        match seed.deserialize(key) {
+            Ok(key) => Ok(Some(key)),
+            Err(_) => {
+                self.slots.clear();
+                Ok(None)
+            }
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (_, schema) = self.slots
+            .pop_front()
+            .ok_or_else(|| <Error as de::Error>::custom("value without a key"))?;
+        seed.deserialize(Tracer { schema })
+    }
+}
+
+/// Enum answered with its first variant
+struct Variant {
+    name: &'static str,
+}
+
+impl<'de> de::EnumAccess<'de> for Variant {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<(T::Value, Self), Error> {
+        let value = seed.deserialize(self.name.into_deserializer())?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Variant {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(Tracer { schema: &mut json!({}) })
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(Tracer { schema: &mut json!({}) }, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_struct(Tracer { schema: &mut json!({}) }, "", fields, visitor)
+    }
+}
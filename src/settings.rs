@@ -0,0 +1,503 @@
+use eframe::egui;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+
+use crate::config::{ColorConfig, DataTagConfig, DisplayConfig, NetworkConfig, RadarConfig};
+use crate::radar::{color_to_hex, try_parse_color};
+use crate::template::{TagContext, Template, Variable};
+use crate::themes;
+use crate::validation::{ConfigError, Validator};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum SettingsTab {
+    #[default]
+    Display,
+    Colors,
+    DataTags,
+    Network,
+}
+
+impl SettingsTab {
+    const ALL: [SettingsTab; 4] = [
+        SettingsTab::Display,
+        SettingsTab::Colors,
+        SettingsTab::DataTags,
+        SettingsTab::Network,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            SettingsTab::Display => "Display",
+            SettingsTab::Colors => "Colors",
+            SettingsTab::DataTags => "Data tags",
+            SettingsTab::Network => "Network",
+        }
+    }
+}
+
+/// Settings window contents, editing a copy of the config
+#[derive(Default)]
+pub struct SettingsEditor {
+    tab: SettingsTab,
+
+    /// Template or color text that does not parse yet, keyed by field
+    /// The config keeps the last valid value until the draft is fixed.
+    drafts: HashMap<String, String>,
+
+    /// Name typed for a new palette entry
+    new_palette_entry: String,
+
+    /// Problems that kept the last edits from being saved to config.toml
+    save_error: Option<ConfigError>,
+}
+
+impl SettingsEditor {
+    /// Report why edits could not be saved, or `None` once they were
+    pub fn set_save_error(&mut self, error: Option<ConfigError>) {
+        self.save_error = error;
+    }
+
+    /// Show the editor, returning true if the config changed
+    /// `colors` are the colors in use, with any theme applied, and `preview`
+    /// is the aircraft tag templates are previewed with
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &mut RadarConfig,
+        colors: &ColorConfig,
+        preview: Option<&TagContext>
+    ) -> bool {
+        ui.horizontal(|ui| {
+            for tab in SettingsTab::ALL {
+                ui.selectable_value(&mut self.tab, tab, tab.label());
+            }
+        });
+        ui.separator();
+
+        if let Some(error) = &self.save_error {
+            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), "Not saved to config.toml:");
+            for issue in &error.issues {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("  {}", issue));
+            }
+            ui.separator();
+        }
+
+        let mut changed = false;
+        egui::ScrollArea::vertical().max_height(480.0).show(ui, |ui| {
+            changed = match self.tab {
+                SettingsTab::Display => display_settings(ui, &mut config.display),
+                SettingsTab::Colors => self.color_settings(ui, &mut config.colors),
+                SettingsTab::DataTags => self.data_tag_settings(ui, &mut config.data_tags, colors, preview),
+                SettingsTab::Network => self.network_settings(ui, &mut config.network),
+            };
+        });
+
+        changed
+    }
+
+    fn data_tag_settings(
+        &mut self,
+        ui: &mut egui::Ui,
+        tags: &mut DataTagConfig,
+        colors: &ColorConfig,
+        preview: Option<&TagContext>
+    ) -> bool {
+        let mut changed = false;
+
+        match preview {
+            Some(ctx) => tag_preview(ui, &tags.parsed_layout(None), colors, ctx),
+            None => {
+                ui.weak("No traffic to preview tags with");
+            }
+        }
+        ui.add_space(4.0);
+
+        egui::Grid
+            ::new("tag_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                changed |= slider(ui, "Line spacing", &mut tags.line_spacing, 0.0..=100.0);
+                changed |= checkbox(ui, "Automatic placement", &mut tags.auto_placement);
+                changed |= slider(ui, "Placement gap", &mut tags.placement_gap, 0.0..=200.0);
+                changed |= slider(ui, "Placement stability", &mut tags.placement_stability, 0.0..=1000.0);
+                changed |= checkbox(ui, "Leader lines", &mut tags.leader_lines);
+            });
+
+        ui.separator();
+        ui.strong("Lines");
+        changed |= self.template_field(ui, "line1", &mut tags.line1, preview);
+        changed |= self.template_field(ui, "line2", &mut tags.line2, preview);
+        changed |= self.optional_template_field(ui, "line3", &mut tags.line3, preview);
+        changed |= self.optional_template_field(ui, "line4", &mut tags.line4, preview);
+
+        ui.separator();
+        ui.strong("Layouts");
+        for (name, layout) in tags.layouts.iter_mut() {
+            egui::CollapsingHeader
+                ::new(name.as_str())
+                .id_source(("tag_layout", name.as_str()))
+                .show(ui, |ui| {
+                    let mut remove = None;
+                    for (i, line) in layout.lines.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("🗑").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.vertical(|ui| {
+                                let key = format!("layouts.{}.{}", name, i);
+                                changed |= self.template_field(ui, &key, line, preview);
+                            });
+                        });
+                    }
+
+                    if let Some(i) = remove {
+                        layout.lines.remove(i);
+                        self.drafts.retain(|key, _| !key.starts_with(&format!("layouts.{}.", name)));
+                        changed = true;
+                    }
+                    if ui.small_button("+ Line").clicked() {
+                        layout.lines.push(String::new());
+                        changed = true;
+                    }
+                });
+        }
+
+        ui.separator();
+        let names: Vec<&str> = Variable::NAMES.iter().map(|(name, _)| *name).collect();
+        ui.small(format!("Variables: {}", names.join(", ")));
+        ui.small("{var:03} pads, {?var}...{/} shows text only when var is set");
+
+        changed
+    }
+
+    /// Edit a template, updating `source` only while the text parses
+    fn template_field(
+        &mut self,
+        ui: &mut egui::Ui,
+        key: &str,
+        source: &mut String,
+        preview: Option<&TagContext>
+    ) -> bool {
+        let mut text = self.drafts.get(key).cloned().unwrap_or_else(|| source.clone());
+
+        ui.horizontal(|ui| {
+            ui.monospace(format!("{:<6}", key.rsplit('.').next().unwrap_or(key)));
+            ui.add(
+                egui::TextEdit
+                    ::singleline(&mut text)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY)
+            );
+        });
+
+        match Template::parse(&text) {
+            Ok(template) => {
+                self.drafts.remove(key);
+                if let Some(ctx) = preview {
+                    ui.monospace(egui::RichText::new(format!("  → {}", template.render(ctx))).weak());
+                }
+                if text != *source {
+                    *source = text;
+                    return true;
+                }
+            }
+            Err(e) => {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("  {}", e));
+                self.drafts.insert(key.to_string(), text);
+            }
+        }
+
+        false
+    }
+
+    /// Edit an optional template line, where an empty line means unset
+    fn optional_template_field(
+        &mut self,
+        ui: &mut egui::Ui,
+        key: &str,
+        source: &mut Option<String>,
+        preview: Option<&TagContext>
+    ) -> bool {
+        let mut text = source.clone().unwrap_or_default();
+        if !self.template_field(ui, key, &mut text, preview) {
+            return false;
+        }
+
+        *source = (!text.is_empty()).then_some(text);
+        true
+    }
+
+    fn color_settings(&mut self, ui: &mut egui::Ui, colors: &mut ColorConfig) -> bool {
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Theme");
+            egui::ComboBox
+                ::from_id_source("theme")
+                .selected_text(colors.theme.as_deref().unwrap_or("None"))
+                .show_ui(ui, |ui| {
+                    changed |= ui.selectable_value(&mut colors.theme, None, "None").changed();
+                    for name in themes::available() {
+                        changed |= ui.selectable_value(&mut colors.theme, Some(name.clone()), name).changed();
+                    }
+                });
+        });
+        if let Some(name) = &colors.theme {
+            ui.weak(format!("The {} theme is in use; the colors below apply without a theme", name));
+        }
+        ui.small(format!("Theme files go in {}", themes::themes_dir().display()));
+
+        ui.separator();
+        ui.strong("Scope");
+        let palette = colors.palette.clone();
+        let fields = [
+            ("Background", "background", &mut colors.background),
+            ("Target", "target", &mut colors.target),
+            ("Selected target", "target_selected", &mut colors.target_selected),
+            ("Emergency target", "target_emergency", &mut colors.target_emergency),
+            ("Tag text", "tag_text", &mut colors.tag_text),
+            ("History", "history", &mut colors.history),
+            ("Vectors", "vector", &mut colors.vector),
+            ("Ground", "ground", &mut colors.ground),
+            ("Leader lines", "leader_line", &mut colors.leader_line),
+            ("Wake alert", "wake_alert", &mut colors.wake_alert),
+            ("Filter areas", "filter_area", &mut colors.filter_area),
+            ("Compass rose", "compass", &mut colors.compass),
+        ];
+
+        egui::Grid
+            ::new("color_settings")
+            .num_columns(4)
+            .show(ui, |ui| {
+                for (label, key, value) in fields {
+                    ui.label(label);
+                    changed |= self.color_edit(ui, key, value, &palette);
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+        ui.strong("Palette");
+        let mut remove = None;
+        egui::Grid
+            ::new("palette_settings")
+            .num_columns(4)
+            .show(ui, |ui| {
+                for (name, value) in colors.palette.iter_mut() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("🗑").clicked() {
+                            remove = Some(name.clone());
+                        }
+                        ui.monospace(format!("${}", name));
+                    });
+                    // Entries can't refer to each other
+                    changed |= self.color_edit(ui, &format!("palette.{}", name), value, &BTreeMap::new());
+                    ui.end_row();
+                }
+            });
+        if let Some(name) = remove {
+            colors.palette.remove(&name);
+            self.drafts.remove(&format!("palette.{}", name));
+            changed = true;
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.new_palette_entry).hint_text("name").desired_width(100.0));
+            let name = self.new_palette_entry.trim();
+            let valid = !name.is_empty() && !name.starts_with('$') && !colors.palette.contains_key(name);
+            if ui.add_enabled(valid, egui::Button::new("+ Color")).clicked() {
+                colors.palette.insert(name.to_string(), "white".to_string());
+                self.new_palette_entry.clear();
+                changed = true;
+            }
+        });
+        ui.small("Colors take hex (#RRGGBB or #RRGGBBAA), a name like amber, or $name for a palette entry");
+
+        ui.separator();
+        ui.strong("Interface");
+        let chrome = &mut colors.chrome;
+        let fields = [
+            ("Background", "chrome.background", &mut chrome.background),
+            ("Text", "chrome.text", &mut chrome.text),
+            ("Controls", "chrome.widget", &mut chrome.widget),
+            ("Accent", "chrome.accent", &mut chrome.accent),
+        ];
+
+        egui::Grid
+            ::new("chrome_settings")
+            .num_columns(4)
+            .show(ui, |ui| {
+                changed |= checkbox(ui, "Dark", &mut chrome.dark);
+                for (label, key, value) in fields {
+                    let mut set = value.is_some();
+                    if ui.checkbox(&mut set, label).changed() {
+                        *value = set.then(|| "gray".to_string());
+                        self.drafts.remove(key);
+                        changed = true;
+                    }
+                    if let Some(value) = value {
+                        changed |= self.color_edit(ui, key, value, &palette);
+                    }
+                    ui.end_row();
+                }
+            });
+
+        changed
+    }
+
+    fn network_settings(&mut self, ui: &mut egui::Ui, network: &mut NetworkConfig) -> bool {
+        let mut changed = false;
+
+        egui::Grid
+            ::new("network_settings")
+            .num_columns(3)
+            .show(ui, |ui| {
+                ui.label("WebSocket URL");
+                changed |= self.url_field(ui, "websocket_url", &mut network.websocket_url, &["ws", "wss"]);
+                ui.end_row();
+
+                ui.label("API base URL");
+                changed |= self.url_field(ui, "api_base_url", &mut network.api_base_url, &["http", "https"]);
+                ui.end_row();
+
+                ui.label("Reconnect delay (s)");
+                changed |= ui.add(egui::DragValue::new(&mut network.reconnect_delay_secs).range(1..=3600)).changed();
+                ui.end_row();
+
+                changed |= checkbox(ui, "Main server", &mut network.enable_main_server);
+                changed |= checkbox(ui, "Event server", &mut network.enable_event_server);
+            });
+
+        ui.weak("Network changes apply when the feed next reconnects");
+
+        changed
+    }
+
+    /// Edit a URL, updating `value` only while the text is a URL with one of `schemes`
+    fn url_field(&mut self, ui: &mut egui::Ui, key: &str, value: &mut String, schemes: &[&str]) -> bool {
+        let mut text = self.drafts.get(key).cloned().unwrap_or_else(|| value.clone());
+        ui.text_edit_singleline(&mut text);
+
+        let mut v = Validator::new();
+        v.url("", &text, schemes);
+        match v.finish() {
+            Ok(()) => {
+                self.drafts.remove(key);
+                ui.label("");
+                if text != *value {
+                    *value = text;
+                    return true;
+                }
+            }
+            Err(e) => {
+                let message = e.issues.iter().map(|issue| issue.message.as_str()).collect::<Vec<_>>().join(", ");
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), message);
+                self.drafts.insert(key.to_string(), text);
+            }
+        }
+
+        false
+    }
+
+    /// Edit a color with a picker and as text, updating `value` only while the text is a color
+    fn color_edit(
+        &mut self,
+        ui: &mut egui::Ui,
+        key: &str,
+        value: &mut String,
+        palette: &BTreeMap<String, String>
+    ) -> bool {
+        let resolve = |text: &str| match text.strip_prefix('$') {
+            Some(name) => palette.get(name).and_then(|entry| try_parse_color(entry)),
+            None => try_parse_color(text),
+        };
+
+        let mut changed = false;
+        let mut color = resolve(value).unwrap_or(egui::Color32::WHITE);
+        if ui.color_edit_button_srgba(&mut color).changed() {
+            *value = color_to_hex(color);
+            self.drafts.remove(key);
+            changed = true;
+        }
+
+        let mut text = self.drafts.get(key).cloned().unwrap_or_else(|| value.clone());
+        ui.add(egui::TextEdit::singleline(&mut text).font(egui::TextStyle::Monospace).desired_width(100.0));
+
+        if resolve(&text).is_some() {
+            self.drafts.remove(key);
+            ui.label("");
+            if text != *value {
+                *value = text;
+                changed = true;
+            }
+        } else {
+            let message = match text.strip_prefix('$') {
+                Some(name) => format!("no palette entry \"{}\"", name),
+                None => "not a color".to_string(),
+            };
+            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), message);
+            self.drafts.insert(key.to_string(), text);
+        }
+
+        changed
+    }
+}
+
+/// Tag drawn as it appears on the scope
+fn tag_preview(ui: &mut egui::Ui, templates: &[Template], colors: &ColorConfig, ctx: &TagContext) {
+    egui::Frame
+        ::none()
+        .fill(colors.color(&colors.background))
+        .inner_margin(6.0)
+        .show(ui, |ui| {
+            for line in templates.iter().map(|t| t.render(ctx)).filter(|l| !l.trim().is_empty()) {
+                ui.label(egui::RichText::new(line).monospace().color(colors.color(&colors.tag_text)));
+            }
+        });
+}
+
+fn display_settings(ui: &mut egui::Ui, display: &mut DisplayConfig) -> bool {
+    let mut changed = false;
+
+    egui::Grid
+        ::new("display_settings")
+        .num_columns(2)
+        .show(ui, |ui| {
+            changed |= slider(ui, "Target scale", &mut display.target_scale, 0.1..=10.0);
+            changed |= slider(ui, "Target stroke", &mut display.target_stroke, 0.1..=20.0);
+            changed |= slider(ui, "Tag font size", &mut display.font_size, 4.0..=72.0);
+            changed |= checkbox(ui, "Show data tags", &mut display.show_tags);
+
+            changed |= checkbox(ui, "Show vectors", &mut display.show_vectors);
+            changed |= slider(ui, "Vector minutes", &mut display.vector_minutes, 0.0..=60.0);
+
+            changed |= checkbox(ui, "Show history", &mut display.show_history);
+            changed |= slider(ui, "History interval (s)", &mut display.history_interval_secs, 0.1..=60.0);
+            changed |= slider(ui, "History duration (s)", &mut display.history_duration_secs, 0.0..=3600.0);
+            changed |= slider(ui, "History dot size", &mut display.history_dot_size, 0.0..=20.0);
+            changed |= checkbox(ui, "Fade history", &mut display.history_fade);
+
+            changed |= slider(ui, "Startup rotation (°)", &mut display.rotation, -360.0..=360.0);
+            changed |= slider(ui, "Magnetic variation (°)", &mut display.magnetic_variation, -180.0..=180.0);
+            changed |= checkbox(ui, "Compass rose", &mut display.show_compass_rose);
+
+            changed |= slider(ui, "Camera easing (s)", &mut display.camera_easing_secs, 0.0..=10.0);
+            changed |= checkbox(ui, "Follow track up", &mut display.follow_track_up);
+        });
+
+    changed
+}
+
+fn slider<N: egui::emath::Numeric>(ui: &mut egui::Ui, label: &str, value: &mut N, range: RangeInclusive<N>) -> bool {
+    ui.label(label);
+    let changed = ui.add(egui::Slider::new(value, range)).changed();
+    ui.end_row();
+    changed
+}
+
+fn checkbox(ui: &mut egui::Ui, label: &str, value: &mut bool) -> bool {
+    ui.label(label);
+    let changed = ui.checkbox(value, "").changed();
+    ui.end_row();
+    changed
+}
@@ -1,8 +1,21 @@
 use parking_lot::RwLock;
-use std::collections::HashMap;
-
-use crate::config::RadarConfig;
+use std::collections::{HashMap, HashSet};
+
+use crate::aircraft_db::classify;
+use crate::alerts::{ current_alerts, Alert };
+use crate::annotations::Annotation;
+use crate::config::{ ColorConfig, RadarConfig };
+use crate::profiles;
+use crate::retention::{ expired, now_millis, retain_seen, Kept, LAST_SEEN_SAVE_INTERVAL_MS };
+use crate::strips::{ update_strips, FlightStrip, StripBay };
+use crate::themes;
 use crate::types::{ Atis, ControllerPosition, FlightPlan, TrackedAircraft };
+use crate::validation::ConfigError;
+use crate::views::{ learn_airports, AirportFix };
+use crate::wake::{ compute_advisories, WakeAdvisory };
+
+/// Seconds of feed data an aircraft can miss before it is dropped
+const STALE_AIRCRAFT_SECS: i64 = 60;
 
 /// Thread-safe radar state shared between GUI and network threads
 pub struct RadarState {
@@ -15,14 +28,66 @@ pub struct RadarState {
     /// ATIS information by airport
     atis: RwLock<HashMap<String, Atis>>,
 
-    /// Configuration (hot-reloadable)
+    /// Per-aircraft data tag offsets set by dragging, keyed by callsign
+    /// Kept when the aircraft drops off the feed so the tag returns in place,
+    /// until it has been gone too long
+    tag_offsets: RwLock<HashMap<String, (f32, f32)>>,
+
+    /// Tag offsets changed since they were last saved
+    tag_offsets_dirty: RwLock<bool>,
+
+    /// Callsigns tracked (assumed) by this controller
+    tracked_by_me: RwLock<HashSet<String>>,
+
+    /// Wake separation advisories keyed by follower callsign
+    wake_advisories: RwLock<HashMap<String, WakeAdvisory>>,
+
+    /// Followers whose infringed wake alert has been acknowledged,
+    /// forgotten once separation is regained
+    acknowledged_alerts: RwLock<HashSet<String>>,
+
+    /// Airport positions learned from traffic, keyed by ICAO code
+    airports: RwLock<HashMap<String, AirportFix>>,
+
+    /// Flight strips keyed by callsign, kept after the aircraft leaves the feed
+    strips: RwLock<HashMap<String, FlightStrip>>,
+
+    /// Strips changed since they were last saved
+    strips_dirty: RwLock<bool>,
+
+    /// When each callsign was last on the feed, for expiring what is kept after it leaves
+    last_seen: RwLock<HashMap<String, i64>>,
+
+    /// When saved files were last marked for saving to bring their last-seen times up to date
+    last_seen_saved: RwLock<i64>,
+
+    /// Controller annotations keyed by callsign, kept after the aircraft leaves the feed
+    annotations: RwLock<HashMap<String, Annotation>>,
+
+    /// Annotations changed since they were last saved
+    annotations_dirty: RwLock<bool>,
+
+    /// Configuration (hot-reloadable), with the active profile applied
     config: RwLock<RadarConfig>,
 
+    /// Configuration as stored in the file, holding the profiles
+    base_config: RwLock<RadarConfig>,
+
+    /// Colors in use: the config's theme, or its own colors without one
+    colors: RwLock<ColorConfig>,
+
+    /// Profile chosen on the command line, used instead of the file's `active_profile`
+    /// `Some(None)` means the base config.
+    profile_override: RwLock<Option<Option<String>>>,
+
+    /// Problems in the config file; the last good configuration stays in use meanwhile
+    config_error: RwLock<Option<String>>,
+
     /// Connection status
     connection_status: RwLock<ConnectionStatus>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ConnectionStatus {
     pub websocket_connected: bool,
     pub last_data_received: Option<i64>,
@@ -30,24 +95,29 @@ pub struct ConnectionStatus {
     pub event_aircraft_count: usize,
 }
 
-impl Default for ConnectionStatus {
-    fn default() -> Self {
-        Self {
-            websocket_connected: false,
-            last_data_received: None,
-            aircraft_count: 0,
-            event_aircraft_count: 0,
-        }
-    }
-}
-
 impl RadarState {
     pub fn new() -> Self {
         Self {
             aircraft: RwLock::new(HashMap::new()),
             controllers: RwLock::new(Vec::new()),
             atis: RwLock::new(HashMap::new()),
+            tag_offsets: RwLock::new(HashMap::new()),
+            tag_offsets_dirty: RwLock::new(false),
+            tracked_by_me: RwLock::new(HashSet::new()),
+            wake_advisories: RwLock::new(HashMap::new()),
+            acknowledged_alerts: RwLock::new(HashSet::new()),
+            airports: RwLock::new(HashMap::new()),
+            strips: RwLock::new(HashMap::new()),
+            strips_dirty: RwLock::new(false),
+            last_seen: RwLock::new(HashMap::new()),
+            last_seen_saved: RwLock::new(now_millis()),
+            annotations: RwLock::new(HashMap::new()),
+            annotations_dirty: RwLock::new(false),
             config: RwLock::new(RadarConfig::default()),
+            base_config: RwLock::new(RadarConfig::default()),
+            colors: RwLock::new(ColorConfig::default()),
+            profile_override: RwLock::new(None),
+            config_error: RwLock::new(None),
             connection_status: RwLock::new(ConnectionStatus::default()),
         }
     }
@@ -55,25 +125,103 @@ impl RadarState {
     // Aircraft management
 
     /// Update aircraft data from API
-    pub fn update_aircraft_batch(&self, aircraft_map: HashMap<String, crate::types::AircraftInfo>) {
+    /// `event` marks aircraft from the event server feed
+    pub fn update_aircraft_batch(
+        &self,
+        aircraft_map: HashMap<String, crate::types::AircraftInfo>,
+        event: bool
+    ) {
+        self.clear_stale_aircraft(STALE_AIRCRAFT_SECS);
+
         let mut aircraft = self.aircraft.write();
         let config = self.config.read();
-        let max_history = config.display.history_length;
+        let interval_ms = (config.display.history_interval_secs * 1000.0) as i64;
+        let duration_ms = (config.display.history_duration_secs * 1000.0) as i64;
+
+        let now = now_millis();
+        let mut last_seen = self.last_seen.write();
 
         // Update existing and add new aircraft
         for (callsign, info) in aircraft_map {
-            aircraft
+            last_seen.insert(callsign.clone(), now);
+            let tracked = aircraft
                 .entry(callsign.clone())
-                .and_modify(|tracked| tracked.update(info.clone(), max_history))
+                .and_modify(|tracked| tracked.update(info.clone(), interval_ms, duration_ms))
                 .or_insert_with(|| TrackedAircraft::new(callsign, info));
+            tracked.is_event = event;
+            tracked.type_info = classify(&config.aircraft_types, &tracked.info);
+        }
+
+        // Re-check wake separation on every update, independent of what is displayed
+        let advisories = compute_advisories(&aircraft, &config.wake, now);
+        self.acknowledged_alerts
+            .write()
+            .retain(|callsign| advisories.get(callsign).is_some_and(|a| a.infringed));
+        *self.wake_advisories.write() = advisories;
+
+        learn_airports(&mut self.airports.write(), &aircraft);
+
+        if update_strips(&mut self.strips.write(), &aircraft, now) {
+            *self.strips_dirty.write() = true;
         }
 
+        self.expire_off_feed(&mut last_seen, now);
+
         // Update connection status
         let mut status = self.connection_status.write();
         status.aircraft_count = aircraft.len();
+        status.event_aircraft_count = aircraft.values().filter(|a| a.is_event).count();
         status.last_data_received = Some(chrono::Utc::now().timestamp_millis());
     }
 
+    /// Drop what is kept for aircraft that have been off the feed too long
+    fn expire_off_feed(&self, last_seen: &mut HashMap<String, i64>, now: i64) {
+        last_seen.retain(|_, seen| !expired(*seen, now));
+
+        // Save now and then so the files know which aircraft are still around
+        let mut saved = self.last_seen_saved.write();
+        let refresh = now - *saved > LAST_SEEN_SAVE_INTERVAL_MS;
+        if refresh {
+            *saved = now;
+        }
+
+        let mut strips = self.strips.write();
+        if retain_seen(&mut strips, last_seen) || (refresh && !strips.is_empty()) {
+            *self.strips_dirty.write() = true;
+        }
+
+        let mut annotations = self.annotations.write();
+        if retain_seen(&mut annotations, last_seen) || (refresh && !annotations.is_empty()) {
+            *self.annotations_dirty.write() = true;
+        }
+
+        let mut tag_offsets = self.tag_offsets.write();
+        if retain_seen(&mut tag_offsets, last_seen) || (refresh && !tag_offsets.is_empty()) {
+            *self.tag_offsets_dirty.write() = true;
+        }
+    }
+
+    /// Get when each callsign was last on the feed, as Unix time in milliseconds
+    pub fn get_last_seen(&self) -> HashMap<String, i64> {
+        self.last_seen.read().clone()
+    }
+
+    /// Remember when the aircraft of loaded entries were last seen, dropping expired entries
+    fn restore_kept<T>(&self, entries: HashMap<String, Kept<T>>) -> HashMap<String, T> {
+        let now = now_millis();
+        let mut last_seen = self.last_seen.write();
+
+        entries
+            .into_iter()
+            .filter(|(_, entry)| !expired(entry.last_seen, now))
+            .map(|(callsign, entry)| {
+                let seen = last_seen.entry(callsign.clone()).or_insert(entry.last_seen);
+                *seen = (*seen).max(entry.last_seen);
+                (callsign, entry.value)
+            })
+            .collect()
+    }
+
     /// Get all tracked aircraft (read-only)
     pub fn get_aircraft(&self) -> HashMap<String, TrackedAircraft> {
         self.aircraft.read().clone()
@@ -85,11 +233,13 @@ impl RadarState {
     }
 
     /// Clear stale aircraft (not updated in last N seconds)
+    /// Age counts up to the latest feed data rather than the clock, so a
+    /// pause in the whole feed doesn't clear everything.
     pub fn clear_stale_aircraft(&self, max_age_secs: i64) {
+        let latest = self.connection_status.read().last_data_received.unwrap_or_else(now_millis);
         let mut aircraft = self.aircraft.write();
-        let now = chrono::Utc::now().timestamp_millis();
 
-        aircraft.retain(|_, tracked| { now - tracked.last_update < max_age_secs * 1000 });
+        aircraft.retain(|_, tracked| { latest - tracked.last_update < max_age_secs * 1000 });
     }
 
     /// Associate flight plan with aircraft
@@ -121,6 +271,7 @@ impl RadarState {
     }
 
     /// Get ATIS for specific airport
+    #[allow(dead_code)]
     pub fn get_atis(&self, airport: &str) -> Option<Atis> {
         self.atis.read().get(airport).cloned()
     }
@@ -130,10 +281,162 @@ impl RadarState {
         self.atis.read().clone()
     }
 
+    /// Get wake separation advisories keyed by follower callsign
+    pub fn get_wake_advisories(&self) -> HashMap<String, WakeAdvisory> {
+        self.wake_advisories.read().clone()
+    }
+
+    /// Acknowledge all current wake alerts
+    pub fn acknowledge_wake_alerts(&self) {
+        let advisories = self.wake_advisories.read();
+        self.acknowledged_alerts
+            .write()
+            .extend(advisories.iter().filter(|(_, a)| a.infringed).map(|(callsign, _)| callsign.clone()));
+    }
+
+    /// Get emergencies and wake alerts, most urgent first
+    pub fn get_alerts(&self) -> Vec<Alert> {
+        let aircraft = self.aircraft.read();
+        let wake_mode = self.config.read().wake.mode;
+        current_alerts(&aircraft, &self.wake_advisories.read(), &self.acknowledged_alerts.read(), wake_mode)
+    }
+
+    /// Get followers whose wake alert has been acknowledged
+    pub fn get_acknowledged_alerts(&self) -> HashSet<String> {
+        self.acknowledged_alerts.read().clone()
+    }
+
+    // Airports
+
+    /// Get airport positions learned from traffic
+    pub fn get_airport_fixes(&self) -> HashMap<String, AirportFix> {
+        self.airports.read().clone()
+    }
+
+    // Data tag offsets
+
+    /// Replace all tag offsets (on startup), dropping those whose aircraft left the feed too long ago
+    pub fn set_tag_offsets(&self, offsets: HashMap<String, Kept<(f32, f32)>>) {
+        let offsets = self.restore_kept(offsets);
+        *self.tag_offsets.write() = offsets;
+    }
+
+    /// Set a manual tag offset for an aircraft (overrides `DataTagConfig::offset`)
+    pub fn set_tag_offset(&self, callsign: &str, offset: (f32, f32)) {
+        self.tag_offsets.write().insert(callsign.to_string(), offset);
+        *self.tag_offsets_dirty.write() = true;
+    }
+
+    /// Return an aircraft's tag to automatic placement
+    pub fn clear_tag_offset(&self, callsign: &str) {
+        if self.tag_offsets.write().remove(callsign).is_some() {
+            *self.tag_offsets_dirty.write() = true;
+        }
+    }
+
+    /// Get all manual tag offsets
+    pub fn get_tag_offsets(&self) -> HashMap<String, (f32, f32)> {
+        self.tag_offsets.read().clone()
+    }
+
+    /// Whether tag offsets changed since the last call, clearing the flag
+    pub fn take_tag_offsets_dirty(&self) -> bool {
+        std::mem::take(&mut *self.tag_offsets_dirty.write())
+    }
+
+    // Tracking
+
+    /// Assume or release an aircraft
+    pub fn toggle_tracked(&self, callsign: &str) {
+        let mut tracked = self.tracked_by_me.write();
+        if !tracked.remove(callsign) {
+            tracked.insert(callsign.to_string());
+        }
+    }
+
+    /// Get callsigns tracked by this controller
+    pub fn get_tracked(&self) -> HashSet<String> {
+        self.tracked_by_me.read().clone()
+    }
+
+    // Flight strips
+
+    /// Replace all strips (on startup), dropping those whose aircraft left the feed too long ago
+    pub fn set_strips(&self, strips: HashMap<String, Kept<FlightStrip>>) {
+        let strips = self.restore_kept(strips);
+        *self.strips.write() = strips;
+    }
+
+    /// Get all flight strips
+    pub fn get_strips(&self) -> HashMap<String, FlightStrip> {
+        self.strips.read().clone()
+    }
+
+    /// Store an edited strip
+    pub fn update_strip(&self, strip: FlightStrip) {
+        self.strips.write().insert(strip.callsign.clone(), strip);
+        *self.strips_dirty.write() = true;
+    }
+
+    /// Move a strip to another bay
+    pub fn move_strip(&self, callsign: &str, bay: StripBay) {
+        if let Some(strip) = self.strips.write().get_mut(callsign) {
+            strip.bay = bay;
+            *self.strips_dirty.write() = true;
+        }
+    }
+
+    /// Remove a strip
+    pub fn remove_strip(&self, callsign: &str) {
+        if self.strips.write().remove(callsign).is_some() {
+            *self.strips_dirty.write() = true;
+        }
+    }
+
+    /// Whether strips changed since the last call, clearing the flag
+    pub fn take_strips_dirty(&self) -> bool {
+        std::mem::take(&mut *self.strips_dirty.write())
+    }
+
+    // Annotations
+
+    /// Replace all annotations (on startup), dropping those whose aircraft left the feed too long ago
+    pub fn set_annotations(&self, annotations: HashMap<String, Kept<Annotation>>) {
+        let annotations = self.restore_kept(annotations);
+        *self.annotations.write() = annotations;
+    }
+
+    /// Get all annotations keyed by callsign
+    pub fn get_annotations(&self) -> HashMap<String, Annotation> {
+        self.annotations.read().clone()
+    }
+
+    /// Get the annotation for an aircraft (empty if none)
+    pub fn get_annotation(&self, callsign: &str) -> Annotation {
+        self.annotations.read().get(callsign).cloned().unwrap_or_default()
+    }
+
+    /// Store an aircraft's annotation, dropping it once empty
+    pub fn set_annotation(&self, callsign: &str, annotation: Annotation) {
+        let mut annotations = self.annotations.write();
+        if annotation.is_empty() {
+            annotations.remove(callsign);
+        } else {
+            annotations.insert(callsign.to_string(), annotation);
+        }
+        *self.annotations_dirty.write() = true;
+    }
+
+    /// Whether annotations changed since the last call, clearing the flag
+    pub fn take_annotations_dirty(&self) -> bool {
+        std::mem::take(&mut *self.annotations_dirty.write())
+    }
+
     // Configuration management
 
     /// Update configuration (hot-reload)
     pub fn update_config(&self, config: RadarConfig) {
+        *self.colors.write() = themes::active(&config.colors);
         *self.config.write() = config;
     }
 
@@ -142,6 +445,51 @@ impl RadarState {
         self.config.read().clone()
     }
 
+    /// Get the colors in use, with any theme applied
+    pub fn get_colors(&self) -> ColorConfig {
+        self.colors.read().clone()
+    }
+
+    /// Load the theme again after its file changed
+    pub fn reload_theme(&self) {
+        themes::reload();
+        let colors = themes::active(&self.config.read().colors);
+        *self.colors.write() = colors;
+    }
+
+    /// Use a config as read from the file, applying its active profile
+    pub fn load_base_config(&self, base: RadarConfig) -> Result<(), ConfigError> {
+        let mut selected = base.clone();
+        if let Some(profile) = self.profile_override.read().clone() {
+            selected.active_profile = profile;
+        }
+
+        let config = profiles::effective(&selected)?;
+        *self.base_config.write() = base;
+        self.update_config(config);
+        Ok(())
+    }
+
+    /// Use a profile regardless of the file's `active_profile`, or follow the file again with `None`
+    pub fn set_profile_override(&self, profile: Option<Option<String>>) {
+        *self.profile_override.write() = profile;
+    }
+
+    /// Get the configuration as stored in the file, before its active profile is applied
+    pub fn get_base_config(&self) -> RadarConfig {
+        self.base_config.read().clone()
+    }
+
+    /// Report problems in the config file, or clear them with `None`
+    pub fn set_config_error(&self, error: Option<String>) {
+        *self.config_error.write() = error;
+    }
+
+    /// Get problems in the config file, if it failed to load
+    pub fn get_config_error(&self) -> Option<String> {
+        self.config_error.read().clone()
+    }
+
     // Connection status
 
     /// Update WebSocket connection status
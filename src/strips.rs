@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::retention::{expired, with_last_seen, Kept};
+use crate::types::{FlightPlan, TrackedAircraft};
+
+/// Strip bay, roughly following the life of a flight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StripBay {
+    /// Filed, not yet cleared
+    Pending,
+    /// Clearance delivered
+    Cleared,
+    Taxi,
+    Airborne,
+    /// Transferred to another controller
+    HandedOff,
+}
+
+impl StripBay {
+    pub const ALL: [StripBay; 5] = [
+        StripBay::Pending,
+        StripBay::Cleared,
+        StripBay::Taxi,
+        StripBay::Airborne,
+        StripBay::HandedOff,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StripBay::Pending => "Pending",
+            StripBay::Cleared => "Cleared",
+            StripBay::Taxi => "Taxi",
+            StripBay::Airborne => "Airborne",
+            StripBay::HandedOff => "Handed off",
+        }
+    }
+}
+
+/// Electronic flight strip
+///
+/// Assigned heading, speed and cleared level live in the aircraft's
+/// annotation so the tag, strip and command line all share them.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FlightStrip {
+    pub callsign: String,
+
+    pub bay: StripBay,
+
+    /// Last flight plan seen, kept so the strip stays readable off the feed
+    #[serde(default)]
+    pub flight_plan: Option<FlightPlan>,
+
+    /// Assigned squawk code (up to four octal digits)
+    #[serde(default)]
+    pub squawk: String,
+
+    /// Free text
+    #[serde(default)]
+    pub notes: String,
+
+    /// Aircraft was airborne at the last update, so only a new takeoff moves the strip
+    #[serde(default)]
+    pub was_airborne: bool,
+}
+
+impl FlightStrip {
+    pub fn new(callsign: String, bay: StripBay) -> Self {
+        Self {
+            callsign,
+            bay,
+            flight_plan: None,
+            squawk: String::new(),
+            notes: String::new(),
+            was_airborne: false,
+        }
+    }
+}
+
+/// Bay a new strip starts in
+fn initial_bay(tracked: &TrackedAircraft) -> StripBay {
+    if tracked.info.is_on_ground.unwrap_or(false) {
+        StripBay::Pending
+    } else {
+        StripBay::Airborne
+    }
+}
+
+/// Add strips for newly filed flights, refresh their plans and move
+/// departures to airborne as they lift off
+///
+/// A strip only moves on takeoff, so the controller can still drag it back
+/// afterwards. Aircraft off the feed too long are skipped so their expired
+/// strips don't return. Returns true if any strip changed.
+pub fn update_strips(
+    strips: &mut HashMap<String, FlightStrip>,
+    aircraft: &HashMap<String, TrackedAircraft>,
+    now: i64
+) -> bool {
+    let mut changed = false;
+
+    for tracked in aircraft.values() {
+        let Some(plan) = &tracked.flight_plan else {
+            continue;
+        };
+        if expired(tracked.last_update, now) {
+            continue;
+        }
+        let airborne = tracked.info.is_on_ground == Some(false);
+
+        let strip = strips
+            .entry(tracked.callsign.clone())
+            .or_insert_with(|| {
+                changed = true;
+                let mut strip = FlightStrip::new(tracked.callsign.clone(), initial_bay(tracked));
+                strip.was_airborne = airborne;
+                strip
+            });
+
+        if strip.flight_plan.as_ref() != Some(plan) {
+            strip.flight_plan = Some(plan.clone());
+            changed = true;
+        }
+
+        if airborne != strip.was_airborne {
+            strip.was_airborne = airborne;
+            if airborne && matches!(strip.bay, StripBay::Pending | StripBay::Cleared | StripBay::Taxi) {
+                strip.bay = StripBay::Airborne;
+            }
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+/// Get the strips file path
+pub fn strips_path() -> PathBuf {
+    crate::config::data_path("strips.json")
+}
+
+/// Load saved strips with when their aircraft were last seen, or none if there is no strips file yet
+pub fn load_strips() -> Result<HashMap<String, Kept<FlightStrip>>> {
+    let path = strips_path();
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .context("Failed to read strips.json")?;
+    let strips: Vec<Kept<FlightStrip>> = serde_json::from_str(&contents)
+        .context("Failed to parse strips.json")?;
+
+    Ok(strips.into_iter().map(|strip| (strip.value.callsign.clone(), strip)).collect())
+}
+
+/// Save strips to file, with when their aircraft were last seen
+pub fn save_strips(strips: &HashMap<String, FlightStrip>, last_seen: &HashMap<String, i64>) -> Result<()> {
+    let strips: Vec<Kept<FlightStrip>> = with_last_seen(strips, last_seen)
+        .into_iter()
+        .map(|(_, strip)| strip)
+        .collect();
+
+    let contents = serde_json::to_string_pretty(&strips)
+        .context("Failed to serialize strips")?;
+
+    std::fs::write(strips_path(), contents)
+        .context("Failed to write strips.json")?;
+
+    Ok(())
+}
@@ -0,0 +1,460 @@
+use thiserror::Error;
+
+use crate::annotations::Annotation;
+use crate::radar::magnetic_bearing;
+use crate::types::TrackedAircraft;
+use crate::wake::{WakeAdvisory, WakeMode};
+
+/// Error produced while parsing a tag template
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum TemplateError {
+    #[error("unknown variable `{name}` at column {column} (available: {available})")]
+    UnknownVariable {
+        name: String,
+        column: usize,
+        available: String,
+    },
+
+    #[error("invalid format spec `{spec}` at column {column}")]
+    InvalidSpec { spec: String, column: usize },
+
+    #[error("unclosed `{{` at column {column}")]
+    Unclosed { column: usize },
+
+    #[error("unexpected `}}` at column {column} (use `}}}}` for a literal brace)")]
+    UnexpectedClose { column: usize },
+
+    #[error("conditional opened at column {column} is never closed with `{{/}}`")]
+    UnclosedConditional { column: usize },
+
+    #[error("`{{/}}` at column {column} has no matching conditional")]
+    UnmatchedEnd { column: usize },
+}
+
+impl TemplateError {
+    /// 1-based column in the template source
+    pub fn column(&self) -> usize {
+        match self {
+            TemplateError::UnknownVariable { column, .. } |
+            TemplateError::InvalidSpec { column, .. } |
+            TemplateError::Unclosed { column } |
+            TemplateError::UnexpectedClose { column } |
+            TemplateError::UnclosedConditional { column } |
+            TemplateError::UnmatchedEnd { column } => *column,
+        }
+    }
+}
+
+/// Variables available in tag templates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variable {
+    Callsign,
+    Altitude,
+    Speed,
+    GroundSpeed,
+    Heading,
+    Type,
+    Departure,
+    Arrival,
+    Rules,
+    RequestedLevel,
+    Player,
+    Emergency,
+    Ground,
+    Category,
+    Wake,
+    Icao,
+    WakeGap,
+    WakeAlert,
+    AssignedHeading,
+    AssignedSpeed,
+    ClearedLevel,
+    Runway,
+    Scratchpad,
+}
+
+impl Variable {
+    /// Template name of every variable, in documentation order
+    pub const NAMES: &'static [(&'static str, Variable)] = &[
+        ("callsign", Variable::Callsign),
+        ("altitude", Variable::Altitude),
+        ("speed", Variable::Speed),
+        ("gs", Variable::GroundSpeed),
+        ("heading", Variable::Heading),
+        ("type", Variable::Type),
+        ("dep", Variable::Departure),
+        ("arr", Variable::Arrival),
+        ("rules", Variable::Rules),
+        ("rfl", Variable::RequestedLevel),
+        ("player", Variable::Player),
+        ("emergency", Variable::Emergency),
+        ("ground", Variable::Ground),
+        ("cat", Variable::Category),
+        ("wtc", Variable::Wake),
+        ("icao", Variable::Icao),
+        ("wake", Variable::WakeGap),
+        ("wake_alert", Variable::WakeAlert),
+        ("ahdg", Variable::AssignedHeading),
+        ("aspd", Variable::AssignedSpeed),
+        ("cfl", Variable::ClearedLevel),
+        ("rwy", Variable::Runway),
+        ("scratch", Variable::Scratchpad),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| *v)
+    }
+
+    /// Resolve the variable for an aircraft
+    fn value(self, ctx: &TagContext) -> Value {
+        let tracked = ctx.tracked;
+        let annotation = ctx.annotation;
+        let plan = tracked.flight_plan.as_ref();
+        let plan_field = |f: fn(&crate::types::FlightPlan) -> &String| {
+            Value::Text(plan.map(|fp| f(fp).clone()).unwrap_or_default())
+        };
+
+        match self {
+            Variable::Callsign => Value::Text(tracked.callsign.clone()),
+            Variable::Altitude => Value::Number((tracked.info.altitude / 100.0) as i64),
+            Variable::Speed => Value::Number(tracked.info.speed as i64),
+            Variable::GroundSpeed => Value::Number(tracked.info.ground_speed as i64),
+            Variable::Heading => Value::Number(
+                magnetic_bearing(tracked.info.heading, ctx.magnetic_variation) as i64
+            ),
+            Variable::Type => Value::Text(tracked.info.aircraft_type.clone()),
+            Variable::Departure => plan_field(|fp| &fp.departing),
+            Variable::Arrival => plan_field(|fp| &fp.arriving),
+            Variable::Rules => plan_field(|fp| &fp.flight_rules),
+            Variable::RequestedLevel => plan_field(|fp| &fp.flight_level),
+            Variable::Player => Value::Text(tracked.info.player_name.clone()),
+            Variable::Emergency => Value::flag(tracked.info.is_emergency_occuring, "EMERG"),
+            Variable::Ground => Value::flag(tracked.info.is_on_ground.unwrap_or(false), "GND"),
+            Variable::Category => Value::Text(tracked.type_info.category.code().to_string()),
+            Variable::Wake => Value::Text(tracked.type_info.wake.code().to_string()),
+            Variable::Icao => Value::Text(tracked.type_info.icao.clone()),
+            Variable::WakeGap => Value::Text(
+                ctx.wake.map(|w| w.gap_text(ctx.wake_mode)).unwrap_or_default()
+            ),
+            Variable::WakeAlert => Value::flag(ctx.wake.is_some_and(|w| w.infringed), "WAKE"),
+            Variable::AssignedHeading => Value::optional(annotation.and_then(|a| a.heading)),
+            Variable::AssignedSpeed => Value::optional(annotation.and_then(|a| a.speed)),
+            Variable::ClearedLevel => Value::optional(
+                annotation.and_then(|a| a.cleared_level).map(|level| level / 100)
+            ),
+            Variable::Runway => Value::Text(annotation.map(|a| a.runway.clone()).unwrap_or_default()),
+            Variable::Scratchpad => Value::Text(
+                annotation.map(|a| a.scratchpad.clone()).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// Everything a template can draw values from
+pub struct TagContext<'a> {
+    pub tracked: &'a TrackedAircraft,
+
+    /// Wake advisory if the aircraft is following another on final
+    pub wake: Option<&'a WakeAdvisory>,
+
+    /// Controller's annotation for the aircraft
+    pub annotation: Option<&'a Annotation>,
+
+    /// How wake gaps are displayed
+    pub wake_mode: WakeMode,
+
+    /// Magnetic variation applied to headings
+    pub magnetic_variation: f64,
+}
+
+enum Value {
+    Number(i64),
+    Text(String),
+}
+
+impl Value {
+    /// Boolean variables render as a short word when set and nothing otherwise
+    fn flag(set: bool, word: &str) -> Self {
+        Value::Text(if set { word.to_string() } else { String::new() })
+    }
+
+    /// Unset numbers render as nothing, so conditionals can test them
+    fn optional(value: Option<impl Into<i64>>) -> Self {
+        value.map_or(Value::Text(String::new()), |v| Value::Number(v.into()))
+    }
+
+    fn is_empty(&self) -> bool {
+        matches!(self, Value::Text(text) if text.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// Padding and width for a field, e.g. `03`, `>6`, `-<8`, `.4`
+#[derive(Debug, Clone, PartialEq, Default)]
+struct FormatSpec {
+    fill: Option<char>,
+    align: Option<Align>,
+    zero_pad: bool,
+    width: usize,
+    max_width: Option<usize>,
+}
+
+impl FormatSpec {
+    fn parse(spec: &str) -> Option<Self> {
+        let mut result = FormatSpec::default();
+        let chars: Vec<char> = spec.chars().collect();
+        let mut i = 0;
+
+        let align_of = |c: char| match c {
+            '<' => Some(Align::Left),
+            '>' => Some(Align::Right),
+            '^' => Some(Align::Center),
+            _ => None,
+        };
+
+        // [[fill]align]
+        if chars.len() >= 2 && let Some(align) = align_of(chars[1]) {
+            result.fill = Some(chars[0]);
+            result.align = Some(align);
+            i = 2;
+        } else if let Some(align) = chars.first().and_then(|c| align_of(*c)) {
+            result.align = Some(align);
+            i = 1;
+        }
+
+        // [0][width]
+        if chars.get(i) == Some(&'0') {
+            result.zero_pad = true;
+            i += 1;
+        }
+        let start = i;
+        while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+            i += 1;
+        }
+        if i > start {
+            result.width = chars[start..i].iter().collect::<String>().parse().ok()?;
+        }
+
+        // [.max_width]
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            let start = i;
+            while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+                i += 1;
+            }
+            result.max_width = Some(chars[start..i].iter().collect::<String>().parse().ok()?);
+        }
+
+        (i == chars.len()).then_some(result)
+    }
+
+    fn apply(&self, value: Value) -> String {
+        // Numbers right-align by default, text left-aligns
+        let default_align = match value {
+            Value::Number(_) => Align::Right,
+            Value::Text(_) => Align::Left,
+        };
+
+        let mut text = match value {
+            Value::Number(n) if self.zero_pad && self.align.is_none() => {
+                let width = self.width;
+                if n < 0 {
+                    format!("-{:0>w$}", n.unsigned_abs(), w = width.saturating_sub(1))
+                } else {
+                    format!("{:0>width$}", n)
+                }
+            }
+            Value::Number(n) => n.to_string(),
+            Value::Text(text) => text,
+        };
+
+        if let Some(max) = self.max_width {
+            text = text.chars().take(max).collect();
+        }
+
+        let len = text.chars().count();
+        if len >= self.width {
+            return text;
+        }
+
+        let pad = self.width - len;
+        let fill = self.fill.unwrap_or(if self.zero_pad { '0' } else { ' ' });
+        let repeat = |n: usize| fill.to_string().repeat(n);
+
+        match self.align.unwrap_or(default_align) {
+            Align::Left => text + &repeat(pad),
+            Align::Right => repeat(pad) + &text,
+            Align::Center => repeat(pad / 2) + &text + &repeat(pad - pad / 2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Field(Variable, FormatSpec),
+    Conditional {
+        variable: Variable,
+        negate: bool,
+        body: Vec<Node>,
+    },
+}
+
+/// Conditional being parsed; the root frame has no condition
+#[derive(Default)]
+struct Frame {
+    condition: Option<(Variable, bool)>,
+    column: usize,
+    body: Vec<Node>,
+}
+
+/// Parsed data tag template
+///
+/// Syntax:
+/// - `{var}` inserts a variable, `{var:spec}` pads it (`03`, `>6`, `^8`, `*<5`, `.4` truncates)
+/// - `{?var}...{/}` renders its body only when `var` is non-empty, `{!var}...{/}` when empty
+/// - `{{` and `}}` are literal braces
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut stack = vec![Frame::default()];
+        let mut text = String::new();
+        let chars: Vec<char> = source.chars().collect();
+        let mut i = 0;
+
+        let flush = |text: &mut String, nodes: &mut Vec<Node>| {
+            if !text.is_empty() {
+                nodes.push(Node::Text(std::mem::take(text)));
+            }
+        };
+
+        while i < chars.len() {
+            let column = i + 1;
+            match chars[i] {
+                '{' if chars.get(i + 1) == Some(&'{') => {
+                    text.push('{');
+                    i += 2;
+                }
+                '}' if chars.get(i + 1) == Some(&'}') => {
+                    text.push('}');
+                    i += 2;
+                }
+                '}' => return Err(TemplateError::UnexpectedClose { column }),
+                '{' => {
+                    let close = chars[i..]
+                        .iter()
+                        .position(|c| *c == '}')
+                        .map(|offset| i + offset)
+                        .ok_or(TemplateError::Unclosed { column })?;
+                    let inner: String = chars[i + 1..close].iter().collect();
+                    i = close + 1;
+
+                    let nodes = &mut stack.last_mut().expect("root frame").body;
+                    flush(&mut text, nodes);
+
+                    if inner == "/" {
+                        if stack.len() == 1 {
+                            return Err(TemplateError::UnmatchedEnd { column });
+                        }
+                        let frame = stack.pop().expect("checked above");
+                        let (variable, negate) = frame.condition.expect("only the root has no condition");
+                        stack
+                            .last_mut()
+                            .expect("root frame")
+                            .body
+                            .push(Node::Conditional { variable, negate, body: frame.body });
+                    } else if let Some(name) = inner.strip_prefix('?').or_else(|| inner.strip_prefix('!')) {
+                        let variable = lookup(name, column + 1)?;
+                        let negate = inner.starts_with('!');
+                        stack.push(Frame {
+                            condition: Some((variable, negate)),
+                            column,
+                            body: Vec::new(),
+                        });
+                    } else {
+                        let (name, spec) = match inner.split_once(':') {
+                            Some((name, spec)) => {
+                                let parsed = FormatSpec::parse(spec).ok_or_else(|| {
+                                    TemplateError::InvalidSpec {
+                                        spec: spec.to_string(),
+                                        column,
+                                    }
+                                })?;
+                                (name, parsed)
+                            }
+                            None => (inner.as_str(), FormatSpec::default()),
+                        };
+                        nodes.push(Node::Field(lookup(name.trim(), column + 1)?, spec));
+                    }
+                }
+                c => {
+                    text.push(c);
+                    i += 1;
+                }
+            }
+        }
+
+        let frame = stack.pop().expect("root frame");
+        if frame.condition.is_some() {
+            return Err(TemplateError::UnclosedConditional { column: frame.column });
+        }
+
+        let mut nodes = frame.body;
+        flush(&mut text, &mut nodes);
+
+        Ok(Self { nodes })
+    }
+
+    /// Template that renders `text` verbatim
+    pub fn literal(text: &str) -> Self {
+        Self {
+            nodes: vec![Node::Text(text.to_string())],
+        }
+    }
+
+    /// Render the template for an aircraft
+    pub fn render(&self, ctx: &TagContext) -> String {
+        let mut out = String::new();
+        render_nodes(&self.nodes, ctx, &mut out);
+        out
+    }
+}
+
+fn lookup(name: &str, column: usize) -> Result<Variable, TemplateError> {
+    Variable::from_name(name).ok_or_else(|| TemplateError::UnknownVariable {
+        name: name.to_string(),
+        column,
+        available: Variable::NAMES
+            .iter()
+            .map(|(n, _)| *n)
+            .collect::<Vec<_>>()
+            .join(", "),
+    })
+}
+
+fn render_nodes(nodes: &[Node], ctx: &TagContext, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Field(variable, spec) => out.push_str(&spec.apply(variable.value(ctx))),
+            Node::Conditional { variable, negate, body } => {
+                if variable.value(ctx).is_empty() == *negate {
+                    render_nodes(body, ctx, out);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use eframe::egui;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::config::{data_path, ColorConfig};
+use crate::validation::{ConfigError, Validator};
+
+/// Themes that ship with the radar, as they would be written in a theme file
+const BUILT_IN: [(&str, &str); 2] = [
+    ("night", NIGHT),
+    ("day", DAY),
+];
+
+/// Dim colors for a darkened room
+const NIGHT: &str = r##"
+background = "#05070C"
+target = "$green"
+target_selected = "$amber"
+target_emergency = "#C83232"
+tag_text = "$green"
+history = "#1F5A2A80"
+vector = "#2A5C8C"
+ground = "#4A4A4A"
+leader_line = "#1F5A2A"
+wake_alert = "#B0601C"
+filter_area = "#1A2230"
+compass = "#1E2834"
+
+[palette]
+green = "#3C9A4C"
+amber = "#B8921E"
+
+[chrome]
+dark = true
+background = "#0B0E14"
+text = "#8A9098"
+widget = "#151A22"
+accent = "#2A4A6A"
+"##;
+
+/// High-contrast colors for a bright room
+const DAY: &str = r##"
+background = "#F2F2EE"
+target = "black"
+target_selected = "$blue"
+target_emergency = "#D00000"
+tag_text = "black"
+history = "#00000080"
+vector = "$blue"
+ground = "#707070"
+leader_line = "#404040"
+wake_alert = "#C05000"
+filter_area = "#A0B0C0"
+compass = "#B0B8C0"
+
+[palette]
+blue = "#0050C8"
+
+[chrome]
+dark = false
+background = "#FFFFFF"
+text = "#000000"
+accent = "#0050C8"
+"##;
+
+/// Themes loaded so far by name, or why they failed to load
+/// Kept until theme files change, so config updates don't read them again.
+static LOADED: Mutex<BTreeMap<String, Result<ColorConfig, String>>> = parking_lot::const_mutex(BTreeMap::new());
+
+/// Folder next to config.toml holding theme files
+pub fn themes_dir() -> PathBuf {
+    data_path("themes")
+}
+
+/// Names of the built-in themes and the theme files, sorted
+pub fn available() -> Vec<String> {
+    let mut names: Vec<String> = BUILT_IN.iter().map(|(name, _)| name.to_string()).collect();
+
+    if let Ok(entries) = std::fs::read_dir(themes_dir()) {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == "toml") &&
+                let Some(name) = path.file_stem().and_then(|stem| stem.to_str())
+            {
+                names.push(name.to_string());
+            }
+        }
+    }
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Load a theme, preferring a file in the themes folder over a built-in of the same name
+///
+/// Each theme is read once and then served from memory until `reload`.
+pub fn load(name: &str) -> Result<ColorConfig> {
+    LOADED
+        .lock()
+        .entry(name.to_string())
+        .or_insert_with(|| read(name).map_err(|e| format!("{:#}", e)))
+        .clone()
+        .map_err(anyhow::Error::msg)
+}
+
+/// Forget loaded themes, so they are read again after their files changed
+pub fn reload() {
+    LOADED.lock().clear();
+}
+
+/// Read and check a theme from its file or the built-ins
+fn read(name: &str) -> Result<ColorConfig> {
+    let path = themes_dir().join(format!("{}.toml", name));
+    let (source, contents) = match std::fs::read_to_string(&path) {
+        Ok(contents) => (format!("themes/{}.toml", name), contents),
+        Err(_) => {
+            let (_, contents) = BUILT_IN
+                .iter()
+                .find(|(built_in, _)| *built_in == name)
+                .with_context(|| format!("no theme \"{}\", expected night, day or a file in {}", name, themes_dir().display()))?;
+            (format!("built-in theme {}", name), contents.to_string())
+        }
+    };
+
+    parse(&contents).map_err(|e| {
+        let issues: Vec<String> = e.issues.iter().map(ToString::to_string).collect();
+        anyhow::anyhow!("{}: {}", source, issues.join("; "))
+    })
+}
+
+/// Parse and check a theme file, a `[colors]` table on its own
+fn parse(contents: &str) -> Result<ColorConfig, ConfigError> {
+    let colors: ColorConfig = toml::from_str(contents).map_err(|e| ConfigError::from_toml(&e, contents))?;
+
+    let mut v = Validator::new();
+    if colors.theme.is_some() {
+        v.error("theme", "themes can't use another theme");
+    }
+    v.set_palette(colors.palette.keys());
+    colors.validate(&mut v);
+    v.finish().map_err(|e| e.located(contents))?;
+
+    Ok(colors)
+}
+
+/// Colors to draw with: the theme's if one is set and loads, otherwise the config's own
+///
+/// Palette entries of the config stay available under a theme, so rule colors
+/// referring to them keep working.
+pub fn active(colors: &ColorConfig) -> ColorConfig {
+    let Some(name) = &colors.theme else {
+        return colors.clone();
+    };
+
+    match load(name) {
+        Ok(mut theme) => {
+            for (key, value) in &colors.palette {
+                theme.palette.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+            theme
+        }
+        Err(e) => {
+            tracing::warn!("Failed to load theme: {:#}", e);
+            colors.clone()
+        }
+    }
+}
+
+/// egui look for the chrome colors
+pub fn visuals(colors: &ColorConfig) -> egui::Visuals {
+    let chrome = &colors.chrome;
+    let mut visuals = if chrome.dark { egui::Visuals::dark() } else { egui::Visuals::light() };
+    let color = |value: &Option<String>| value.as_deref().and_then(|value| colors.resolve(value));
+
+    if let Some(background) = color(&chrome.background) {
+        visuals.panel_fill = background;
+        visuals.window_fill = background;
+    }
+    if let Some(text) = color(&chrome.text) {
+        visuals.override_text_color = Some(text);
+    }
+    if let Some(widget) = color(&chrome.widget) {
+        visuals.widgets.inactive.bg_fill = widget;
+        visuals.widgets.inactive.weak_bg_fill = widget;
+    }
+    if let Some(accent) = color(&chrome.accent) {
+        visuals.selection.bg_fill = accent;
+        visuals.hyperlink_color = accent;
+    }
+
+    visuals
+}
@@ -1,5 +1,7 @@
 use serde::{ Deserialize, Serialize };
-use std::collections::HashMap;
+use std::collections::{ HashMap, VecDeque };
+
+use crate::aircraft_db::AircraftTypeInfo;
 
 /// WebSocket message envelope
 #[derive(Debug, Clone, Deserialize)]
@@ -9,6 +11,7 @@ pub struct WsMessage {
     /// Data payload (varies by event type)
     pub d: serde_json::Value,
     /// Timestamp (ISO 8601)
+    #[allow(dead_code)]
     pub s: Option<String>,
 }
 
@@ -65,7 +68,7 @@ pub struct Position {
 }
 
 /// Flight plan information
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct FlightPlan {
     #[serde(rename = "robloxName")]
     pub roblox_name: String,
@@ -132,6 +135,10 @@ pub struct Atis {
     pub editor: Option<String>,
 }
 
+/// Feed time an aircraft can go without updates before it counts as off the feed,
+/// a few feed intervals
+pub const OFF_FEED_AFTER_MS: i64 = 10_000;
+
 /// Internal state for a tracked aircraft with history
 #[derive(Debug, Clone)]
 pub struct TrackedAircraft {
@@ -145,53 +152,107 @@ pub struct TrackedAircraft {
     pub flight_plan: Option<FlightPlan>,
 
     /// History trail positions (for drawing "comet tail")
-    /// Stores (x, y, timestamp) tuples
-    pub history: Vec<(f64, f64, i64)>,
+    /// Stores (x, y, timestamp) tuples sampled at a fixed interval, oldest first
+    pub history: VecDeque<(f64, f64, i64)>,
 
     /// Last update timestamp
     pub last_update: i64,
 
     /// Emergency flash state (for animation)
+    #[allow(dead_code)]
     pub emergency_flash: bool,
+
+    /// Whether the aircraft comes from the event server
+    pub is_event: bool,
+
+    /// Smoothed vertical speed in feet per minute
+    pub vertical_speed: f64,
+
+    /// Category, wake class and ICAO designator from the type database
+    pub type_info: AircraftTypeInfo,
+
+    /// Seen off the ground since tracking started, so on the ground it has landed
+    pub has_been_airborne: bool,
+}
+
+/// Coarse flight phase derived from ground state and vertical speed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlightPhase {
+    Ground,
+    Climbing,
+    Descending,
+    Level,
 }
 
 impl TrackedAircraft {
     pub fn new(callsign: String, info: AircraftInfo) -> Self {
+        let has_been_airborne = info.is_on_ground == Some(false);
         Self {
             callsign,
             info,
             flight_plan: None,
-            history: Vec::new(),
+            history: VecDeque::new(),
             last_update: chrono::Utc::now().timestamp_millis(),
             emergency_flash: false,
+            is_event: false,
+            vertical_speed: 0.0,
+            type_info: AircraftTypeInfo::default(),
+            has_been_airborne,
         }
     }
 
-    /// Update aircraft info and add to history trail
-    pub fn update(&mut self, info: AircraftInfo, max_history: usize) {
-        let now = chrono::Utc::now().timestamp_millis();
+    /// Current flight phase
+    pub fn phase(&self) -> FlightPhase {
+        // Climb/descent threshold in feet per minute
+        const LEVEL_BAND: f64 = 300.0;
+
+        if self.info.is_on_ground.unwrap_or(false) {
+            FlightPhase::Ground
+        } else if self.vertical_speed > LEVEL_BAND {
+            FlightPhase::Climbing
+        } else if self.vertical_speed < -LEVEL_BAND {
+            FlightPhase::Descending
+        } else {
+            FlightPhase::Level
+        }
+    }
 
-        // Add current position to history if it's different enough
-        if self.should_add_history(&info) {
-            self.history.push((self.info.position.x, self.info.position.y, self.last_update));
+    /// Whether the aircraft missed the feed updates up to `latest`, the time of the newest feed data
+    pub fn is_off_feed(&self, latest: i64) -> bool {
+        latest - self.last_update > OFF_FEED_AFTER_MS
+    }
 
-            // Trim history to max size
-            if self.history.len() > max_history {
-                self.history.remove(0);
-            }
+    /// Update aircraft info and sample the history trail
+    /// A point is recorded every `interval_ms` and kept for `duration_ms`
+    pub fn update(&mut self, info: AircraftInfo, interval_ms: i64, duration_ms: i64) {
+        let now = chrono::Utc::now().timestamp_millis();
+
+        // Smooth vertical speed, the feed only updates every few seconds
+        let elapsed_min = (now - self.last_update) as f64 / 60_000.0;
+        if elapsed_min > 0.0 {
+            let rate = (info.altitude - self.info.altitude) / elapsed_min;
+            self.vertical_speed += (rate - self.vertical_speed) * 0.3;
         }
 
+        self.has_been_airborne |= info.is_on_ground == Some(false);
         self.info = info;
         self.last_update = now;
+        self.sample_history(now, interval_ms, duration_ms);
     }
 
-    /// Determine if we should add a new history point
-    fn should_add_history(&self, new_info: &AircraftInfo) -> bool {
-        let dx = new_info.position.x - self.info.position.x;
-        let dy = new_info.position.y - self.info.position.y;
-        let distance = (dx * dx + dy * dy).sqrt();
+    /// Record the current position if a sample is due and drop expired points
+    fn sample_history(&mut self, now: i64, interval_ms: i64, duration_ms: i64) {
+        let due = self.history
+            .back()
+            .is_none_or(|(_, _, timestamp)| now - timestamp >= interval_ms);
+
+        if due {
+            self.history.push_back((self.info.position.x, self.info.position.y, now));
+        }
 
-        // Add point if moved more than 100 studs (~54 meters)
-        distance > 100.0
+        while self.history.front().is_some_and(|(_, _, timestamp)| now - timestamp > duration_ms) {
+            self.history.pop_front();
+        }
     }
 }
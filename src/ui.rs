@@ -1,11 +1,25 @@
 use eframe::egui;
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{ Duration, Instant };
 use tokio::runtime::Runtime;
 
-use crate::config::RadarConfig;
-use crate::radar::{ parse_color, Projection, RadarRenderer };
+use crate::annotations::Annotation;
+use crate::commands::{ self, Command, FilterCommand, ViewCommand };
+use crate::config::{ ColorConfig, RadarConfig };
+use crate::filters::{ FilterMode, Visibility };
+use crate::keybindings::Action;
+use crate::profiles;
+use crate::radar::{ magnetic_bearing, Projection, RadarRenderer, Scene };
+use crate::wake::STUDS_PER_NM;
+use crate::settings::SettingsEditor;
 use crate::state::RadarState;
+use crate::strips::{ FlightStrip, StripBay };
+use crate::template::TagContext;
+use crate::themes;
+use crate::types::TrackedAircraft;
+use crate::viewport::{ ViewportConfig, ViewportKind };
+use crate::views::{ CameraView, ViewPreset };
 
 /// Main radar application
 pub struct RadarApp {
@@ -15,11 +29,14 @@ pub struct RadarApp {
     /// Current configuration
     config: RadarConfig,
 
-    /// Coordinate projection
-    projection: Projection,
+    /// Colors in use, from the config's theme if it has one
+    colors: ColorConfig,
 
-    /// Radar renderer
-    renderer: RadarRenderer,
+    /// Main scope
+    scope: Scope,
+
+    /// Extra insets and windows
+    viewports: Vec<Viewport>,
 
     /// Tokio runtime for async operations
     _runtime: Arc<Runtime>,
@@ -42,28 +59,102 @@ struct UiState {
     /// Show settings panel
     show_settings: bool,
 
-    /// Last mouse position for panning
-    last_mouse_pos: Option<egui::Pos2>,
+    /// Show the flight strip panel
+    show_strips: bool,
+
+    /// Selected aircraft, shared by all scopes
+    selected_aircraft: Option<String>,
+
+    /// Callsign of the data tag currently being dragged
+    dragging_tag: Option<String>,
+
+    /// Aircraft whose annotation popup is open, and where
+    annotating: Option<(String, egui::Pos2)>,
+
+    /// Text in the command line
+    command_input: String,
+
+    /// Entered commands, oldest first
+    command_history: Vec<String>,
+
+    /// Position while browsing history with the arrow keys
+    history_index: Option<usize>,
+
+    /// Result of the last command, or an error
+    command_feedback: Option<Result<String, String>>,
+
+    /// Move the command line cursor to the end next frame
+    command_cursor_to_end: bool,
+
+    /// Range and bearing ruler, while active
+    measure: Option<Measure>,
+
+    /// Config changed from the UI and needs writing to disk
+    config_dirty: bool,
+
+    /// Settings window editor state
+    settings: SettingsEditor,
 
-    /// Whether we're currently panning
-    is_panning: bool,
+    /// Name typed for saving the current view
+    new_view_name: String,
+
+    /// Main camera seen last frame and since when it hasn't moved
+    last_camera: Option<(CameraView, Instant)>,
+
+    /// Main camera as last written to last_view.json
+    saved_camera: Option<CameraView>,
+}
+
+/// Range and bearing ruler between two points in studs
+#[derive(Debug, Default, Clone, Copy)]
+struct Measure {
+    start: Option<(f64, f64)>,
+    end: Option<(f64, f64)>,
 }
 
+/// Widget id of the command line input
+const COMMAND_LINE_ID: &str = "command_line_input";
+
+/// How long the main camera must stay still before it is saved as the last view
+const LAST_VIEW_SAVE_DELAY: Duration = Duration::from_secs(2);
+
+/// Share of the scope height moved by one pan key press
+const KEY_PAN_FRACTION: f32 = 0.1;
+
 impl RadarApp {
     pub fn new(
-        _cc: &eframe::CreationContext,
+        cc: &eframe::CreationContext,
         state: Arc<RadarState>,
         config: RadarConfig,
         runtime: Arc<Runtime>
     ) -> Self {
+        let mut projection = Projection::new(1920.0, 1080.0);
+        projection.set_rotation(config.display.rotation);
+        let mut saved_camera = None;
+        if config.views.restore_last {
+            match crate::views::load_last_view() {
+                Ok(Some(last)) => {
+                    last.apply_to(&mut projection);
+                    saved_camera = Some(last);
+                }
+                Ok(None) => {}
+                Err(e) => tracing::error!("Failed to load last view: {}", e),
+            }
+        }
+
+        let colors = state.get_colors();
+        cc.egui_ctx.set_visuals(themes::visuals(&colors));
+
         Self {
             state,
             config: config.clone(),
-            projection: Projection::new(1920.0, 1080.0),
-            renderer: RadarRenderer::new(),
+            colors,
+            scope: Scope::new(projection),
+            viewports: config.viewports.iter().cloned().map(Viewport::new).collect(),
             _runtime: runtime,
             ui_state: UiState {
                 show_sidebar: true,
+                saved_camera,
                 ..Default::default()
             },
             start_time: Instant::now(),
@@ -82,6 +173,14 @@ impl eframe::App for RadarApp {
             self.config.fonts.ui_font != new_config.fonts.ui_font;
 
         self.config = new_config;
+        self.sync_viewports();
+
+        // Recolor the interface when the theme or chrome colors change
+        let colors = self.state.get_colors();
+        if colors != self.colors {
+            ctx.set_visuals(themes::visuals(&colors));
+            self.colors = colors;
+        }
 
         // Note: Font file changes require restart, but font sizes update immediately
         if fonts_changed {
@@ -96,6 +195,14 @@ impl eframe::App for RadarApp {
             self.render_top_panel(ui);
         });
 
+        // Banner while config.toml has problems
+        let config_error = self.state.get_config_error();
+        if let Some(error) = &config_error {
+            egui::TopBottomPanel::top("config_error").show(ctx, |ui| {
+                Self::render_config_error(ui, error);
+            });
+        }
+
         // Side panel - aircraft list
         if self.ui_state.show_sidebar {
             egui::SidePanel
@@ -106,6 +213,21 @@ impl eframe::App for RadarApp {
                 });
         }
 
+        // Command line
+        egui::TopBottomPanel::bottom("command_line").show(ctx, |ui| {
+            self.render_command_line(ui);
+        });
+
+        // Flight strips
+        if self.ui_state.show_strips {
+            egui::SidePanel
+                ::right("strips")
+                .default_width(360.0)
+                .show(ctx, |ui| {
+                    self.render_strips(ui);
+                });
+        }
+
         // Settings panel
         if self.ui_state.show_settings {
             egui::Window
@@ -120,6 +242,53 @@ impl eframe::App for RadarApp {
         egui::CentralPanel::default().show(ctx, |ui| {
             self.render_radar(ui);
         });
+
+        // Insets and extra windows
+        self.render_viewports(ctx);
+        self.save_viewport_layout();
+        self.save_last_view();
+
+        self.handle_keyboard(ctx);
+
+        self.render_annotation_editor(ctx);
+
+        // Persist annotations once the user has finished typing
+        if
+            !ctx.wants_keyboard_input() &&
+            self.state.take_annotations_dirty() &&
+            let Err(e) = crate::annotations::save_annotations(&self.state.get_annotations(), &self.state.get_last_seen())
+        {
+            tracing::error!("Failed to save annotations: {}", e);
+        }
+
+        // Persist strip changes once the user has finished typing
+        if
+            !ctx.wants_keyboard_input() &&
+            self.state.take_strips_dirty() &&
+            let Err(e) = crate::strips::save_strips(&self.state.get_strips(), &self.state.get_last_seen())
+        {
+            tracing::error!("Failed to save flight strips: {}", e);
+        }
+
+        // Persist dragged tags once the drag is over
+        if
+            !ctx.input(|i| i.pointer.any_down()) &&
+            self.state.take_tag_offsets_dirty() &&
+            let Err(e) = crate::labels::save_tag_offsets(&self.state.get_tag_offsets(), &self.state.get_last_seen())
+        {
+            tracing::error!("Failed to save tag offsets: {}", e);
+        }
+
+        // Persist UI config changes once the user lets go of any control and stops typing
+        // Held back while config.toml has problems, so the user's edits there aren't overwritten
+        if
+            self.ui_state.config_dirty &&
+            config_error.is_none() &&
+            !ctx.wants_keyboard_input() &&
+            !ctx.input(|i| i.pointer.any_down())
+        {
+            self.persist_config();
+        }
     }
 }
 
@@ -146,9 +315,41 @@ impl RadarApp {
 
             ui.label(format!("Aircraft: {}", status.aircraft_count));
 
+            if status.event_aircraft_count > 0 {
+                ui.label(format!("(event: {})", status.event_aircraft_count));
+            }
+
+            let acknowledged = self.state.get_acknowledged_alerts();
+            let wake_alerts = self.state
+                .get_wake_advisories()
+                .iter()
+                .filter(|(callsign, a)| a.infringed && !acknowledged.contains(*callsign))
+                .count();
+            if wake_alerts > 0 {
+                ui.separator();
+                ui.colored_label(
+                    self.colors.color(&self.colors.wake_alert),
+                    format!("⚠ WAKE: {}", wake_alerts)
+                );
+            }
+
             ui.separator();
 
-            ui.label(format!("Zoom: {:.0} studs/px", self.projection.studs_per_pixel));
+            ui.label(format!("Zoom: {:.0} studs/px", self.scope.projection.studs_per_pixel));
+
+            self.render_rotation_controls(ui);
+
+            self.render_follow_controls(ui);
+
+            ui.separator();
+
+            self.render_filter_controls(ui);
+
+            self.render_viewport_controls(ui);
+
+            self.render_view_controls(ui);
+
+            self.render_profile_controls(ui);
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui.button("⚙ Settings").clicked() {
@@ -158,8 +359,177 @@ impl RadarApp {
                 if ui.button(if self.ui_state.show_sidebar { "◄" } else { "►" }).clicked() {
                     self.ui_state.show_sidebar = !self.ui_state.show_sidebar;
                 }
+
+                if ui.selectable_label(self.ui_state.show_strips, "Strips").clicked() {
+                    self.ui_state.show_strips = !self.ui_state.show_strips;
+                }
+            });
+        });
+    }
+
+    /// Render scope rotation and magnetic variation controls
+    fn render_rotation_controls(&mut self, ui: &mut egui::Ui) {
+        let mut display = self.config.display.clone();
+        let mut changed = false;
+
+        let label = format!("Rot: {:03.0}°", self.scope.projection.rotation);
+        ui.menu_button(label, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Up is");
+                let mut rotation = self.scope.projection.rotation;
+                if
+                    ui
+                        .add(egui::DragValue::new(&mut rotation).speed(1.0).suffix("° true"))
+                        .changed()
+                {
+                    self.scope.rotate_manually(rotation);
+                }
+                if ui.button("North up").clicked() {
+                    self.scope.rotate_manually(0.0);
+                }
+            });
+
+            if ui.button("Save as startup rotation").clicked() {
+                display.rotation = self.scope.projection.rotation;
+                changed = true;
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Magnetic variation");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut display.magnetic_variation)
+                            .speed(0.1)
+                            .range(-180.0..=180.0)
+                            .suffix("° E")
+                    )
+                    .changed();
+            });
+            changed |= ui.checkbox(&mut display.show_compass_rose, "Compass rose").changed();
+        });
+
+        if changed {
+            self.config.display = display;
+            self.state.update_config(self.config.clone());
+            self.ui_state.config_dirty = true;
+        }
+    }
+
+    /// Render the problems found in config.toml
+    fn render_config_error(ui: &mut egui::Ui, error: &str) {
+        let mut lines = error.lines();
+
+        ui.horizontal(|ui| {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 100, 100),
+                format!("⚠ {}", lines.next().unwrap_or_default())
+            );
+            ui.weak("Using the last good configuration; changes made here are saved once the file is fixed.");
+        });
+
+        for line in lines {
+            ui.monospace(line.trim());
+        }
+    }
+
+    /// Render follow mode toggle, track-up and camera easing
+    fn render_follow_controls(&mut self, ui: &mut egui::Ui) {
+        let mut easing = self.config.display.camera_easing_secs;
+        let mut changed = false;
+
+        let label = match &self.scope.follow {
+            Some(follow) => format!("Follow ● {}", follow.callsign),
+            None => "Follow".to_string(),
+        };
+        ui.menu_button(label, |ui| {
+            match &mut self.scope.follow {
+                Some(follow) => {
+                    ui.checkbox(&mut follow.track_up, "Track up");
+                    if ui.button("Stop following").clicked() {
+                        self.scope.follow = None;
+                    }
+                }
+                None => {
+                    let selected = self.ui_state.selected_aircraft.clone();
+                    let text = match &selected {
+                        Some(callsign) => format!("Follow {} (F)", callsign),
+                        None => "Select an aircraft to follow".to_string(),
+                    };
+                    if ui.add_enabled(selected.is_some(), egui::Button::new(text)).clicked() {
+                        self.toggle_follow();
+                    }
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Easing");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut easing).speed(0.05).range(0.0..=5.0).suffix(" s"))
+                    .changed();
+            });
+        });
+
+        if changed {
+            self.config.display.camera_easing_secs = easing;
+            self.state.update_config(self.config.clone());
+            self.ui_state.config_dirty = true;
+        }
+    }
+
+    /// Follow the selected aircraft, or stop following
+    fn toggle_follow(&mut self) {
+        if self.scope.follow.is_some() {
+            self.scope.follow = None;
+        } else if let Some(callsign) = self.ui_state.selected_aircraft.clone() {
+            self.scope.start_follow(callsign, self.config.display.follow_track_up);
+        }
+    }
+
+    /// Render quick toggles for the display filters
+    fn render_filter_controls(&mut self, ui: &mut egui::Ui) {
+        let mut filters = self.config.filters.clone();
+        let mut changed = false;
+
+        let label = if filters.is_active() { "Filters ●" } else { "Filters" };
+        ui.menu_button(label, |ui| {
+            changed |= ui.checkbox(&mut filters.altitude_enabled, "Altitude").changed();
+
+            ui.add_enabled_ui(filters.altitude_enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Floor");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut filters.altitude_floor).speed(100.0).suffix(" ft"))
+                        .changed();
+                    ui.label("Ceiling");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut filters.altitude_ceiling).speed(100.0).suffix(" ft"))
+                        .changed();
+                });
+            });
+
+            if !filters.areas.is_empty() {
+                ui.separator();
+                ui.label("Areas");
+                for area in &mut filters.areas {
+                    changed |= ui.checkbox(&mut area.enabled, &area.name).changed();
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Outside:");
+                changed |= ui.radio_value(&mut filters.mode, FilterMode::Dim, "Dim").changed();
+                changed |= ui.radio_value(&mut filters.mode, FilterMode::Hide, "Hide").changed();
             });
         });
+
+        if changed {
+            self.config.filters = filters;
+            self.state.update_config(self.config.clone());
+            self.ui_state.config_dirty = true;
+        }
     }
 
     /// Render aircraft list sidebar
@@ -181,6 +551,7 @@ impl RadarApp {
                 .max_height(300.0) // adjust as needed
                 .show(ui, |ui| {
                     let aircraft = self.state.get_aircraft();
+                    let tracked_by_me = self.state.get_tracked();
                     let filter = self.ui_state.search_filter.to_lowercase();
                     let mut sorted: Vec<_> = aircraft.values().collect();
                     sorted.sort_by(|a, b| a.callsign.cmp(&b.callsign));
@@ -191,16 +562,17 @@ impl RadarApp {
                         }
 
                         let is_selected =
-                            self.renderer.selected_aircraft.as_ref() == Some(&tracked.callsign);
+                            self.ui_state.selected_aircraft.as_ref() == Some(&tracked.callsign);
 
                         let response = ui.selectable_label(is_selected, &tracked.callsign);
 
                         if response.clicked() {
                             if is_selected {
-                                self.renderer.selected_aircraft = None;
+                                self.ui_state.selected_aircraft = None;
                             } else {
-                                self.renderer.selected_aircraft = Some(tracked.callsign.clone());
-                                self.projection.center = (
+                                self.ui_state.selected_aircraft = Some(tracked.callsign.clone());
+                                self.scope.follow = None;
+                                self.scope.projection.center = (
                                     tracked.info.position.x,
                                     tracked.info.position.y,
                                 );
@@ -208,16 +580,35 @@ impl RadarApp {
                         }
 
                         ui.indent(tracked.callsign.clone(), |ui| {
-                            ui.small(format!("Type: {}", tracked.info.aircraft_type));
+                            ui.small(format!(
+                                "Type: {} ({} {}/{})",
+                                tracked.info.aircraft_type,
+                                tracked.type_info.category.code(),
+                                tracked.type_info.icao,
+                                tracked.type_info.wake.code()
+                            ));
                             ui.small(format!("Alt: {:.0} ft", tracked.info.altitude));
                             ui.small(format!("GS: {:.0} kt", tracked.info.ground_speed));
-                            ui.small(format!("Hdg: {:.0}°", tracked.info.heading));
+                            ui.small(
+                                format!(
+                                    "Hdg: {:03.0}°",
+                                    magnetic_bearing(
+                                        tracked.info.heading,
+                                        self.config.display.magnetic_variation
+                                    )
+                                )
+                            );
                             if let Some(fp) = &tracked.flight_plan {
                                 ui.small(format!("{} → {}", fp.departing, fp.arriving));
                             }
                             if tracked.info.is_emergency_occuring {
                                 ui.colored_label(egui::Color32::RED, "⚠ EMERGENCY");
                             }
+
+                            let is_tracked = tracked_by_me.contains(&tracked.callsign);
+                            if ui.small_button(if is_tracked { "Release" } else { "Assume" }).clicked() {
+                                self.state.toggle_tracked(&tracked.callsign);
+                            }
                         });
 
                         ui.separator();
@@ -245,142 +636,1574 @@ impl RadarApp {
         });
     }
 
-    /// Render settings window
-    fn render_settings(&mut self, ui: &mut egui::Ui) {
-        ui.label("Configuration is managed via config.toml");
-        ui.label("Edit the file to customize the radar display.");
+    /// Render flight strip bays
+    fn render_strips(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Flight strips");
+        ui.small("Drag strips by ☰ to move them between bays");
         ui.separator();
 
-        ui.label(format!("Config file: {}", crate::config::config_path().display()));
-
-        if ui.button("Open Config Folder").clicked() {
-            #[cfg(target_os = "windows")]
-            std::process::Command::new("explorer").arg(".").spawn().ok();
+        let strips = self.state.get_strips();
+
+        egui::ScrollArea
+            ::vertical()
+            .id_source("strips_scroll")
+            .show(ui, |ui| {
+                for bay in StripBay::ALL {
+                    let mut bay_strips: Vec<&FlightStrip> = strips
+                        .values()
+                        .filter(|strip| strip.bay == bay)
+                        .collect();
+                    bay_strips.sort_by(|a, b| a.callsign.cmp(&b.callsign));
+
+                    ui.strong(format!("{} ({})", bay.label(), bay_strips.len()));
+
+                    let frame = egui::Frame::group(ui.style()).inner_margin(4.0);
+                    let (_, dropped) = ui.dnd_drop_zone::<String, _>(frame, |ui| {
+                        ui.set_min_size(egui::vec2(ui.available_width(), 20.0));
+                        for strip in bay_strips {
+                            self.render_strip(ui, strip);
+                        }
+                    });
 
-            #[cfg(target_os = "macos")]
-            std::process::Command::new("open").arg(".").spawn().ok();
+                    if let Some(callsign) = dropped {
+                        self.state.move_strip(&callsign, bay);
+                    }
 
-            #[cfg(target_os = "linux")]
-            std::process::Command::new("xdg-open").arg(".").spawn().ok();
-        }
+                    ui.add_space(4.0);
+                }
+            });
     }
 
-    /// Render main radar display
-    fn render_radar(&mut self, ui: &mut egui::Ui) {
-        let rect = ui.available_rect_before_wrap();
+    /// Render one flight strip with its editable fields
+    fn render_strip(&mut self, ui: &mut egui::Ui, strip: &FlightStrip) {
+        let mut edited = strip.clone();
+        let annotation = self.state.get_annotation(&strip.callsign);
+        let mut edited_annotation = annotation.clone();
+        let is_selected = self.ui_state.selected_aircraft.as_ref() == Some(&strip.callsign);
+        let mut remove = false;
+
+        egui::Frame
+            ::none()
+            .fill(ui.visuals().extreme_bg_color)
+            .inner_margin(4.0)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.dnd_drag_source(
+                        egui::Id::new(("strip", &strip.callsign)),
+                        strip.callsign.clone(),
+                        |ui| {
+                            ui.label("☰");
+                        }
+                    );
 
-        // Update projection screen size
-        self.projection.update_screen_size(rect.width(), rect.height());
+                    let callsign = egui::RichText::new(&strip.callsign).monospace().strong();
+                    if ui.selectable_label(is_selected, callsign).clicked() {
+                        self.ui_state.selected_aircraft = (!is_selected).then(|| strip.callsign.clone());
+                    }
 
-        // Draw background
-        let bg_color = parse_color(&self.config.colors.background);
-        ui.painter().rect_filled(rect, 0.0, bg_color);
+                    if let Some(fp) = &strip.flight_plan {
+                        ui.small(
+                            format!(
+                                "{} {} {}→{} {}",
+                                fp.aircraft,
+                                fp.flight_rules,
+                                fp.departing,
+                                fp.arriving,
+                                fp.flight_level
+                            )
+                        );
+                    }
 
-        // Handle input
-        self.handle_radar_input(ui, rect);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        remove = ui.small_button("✕").on_hover_text("Remove strip").clicked();
+                    });
+                });
 
-        // Get current aircraft
-        let aircraft = self.state.get_aircraft();
+                ui.horizontal(|ui| {
+                    annotation_fields(ui, &mut edited_annotation);
+                });
 
-        // Get current time for animations
-        let time_millis = self.start_time.elapsed().as_millis() as i64;
+                ui.horizontal(|ui| {
+                    ui.small("SQK");
+                    let mut squawk = edited.squawk.clone();
+                    if ui.add(egui::TextEdit::singleline(&mut squawk).desired_width(36.0)).changed() {
+                        // Squawks are four octal digits
+                        squawk.retain(|c| ('0'..='7').contains(&c));
+                        squawk.truncate(4);
+                        edited.squawk = squawk;
+                    }
 
-        // Render radar
-        self.renderer.render(
-            ui.painter(),
-            &self.projection,
-            &aircraft,
-            &self.config.display,
-            &self.config.colors,
-            &self.config.data_tags,
-            time_millis
-        );
+                    ui.add(
+                        egui::TextEdit
+                            ::singleline(&mut edited.notes)
+                            .hint_text("Notes")
+                            .desired_width(f32::INFINITY)
+                    );
+                });
+            });
 
-        // Draw center crosshair
-        self.draw_center_crosshair(ui, rect);
+        if remove {
+            self.state.remove_strip(&strip.callsign);
+        } else if edited != *strip {
+            self.state.update_strip(edited);
+        }
+
+        if edited_annotation != annotation {
+            self.state.set_annotation(&strip.callsign, edited_annotation);
+        }
     }
 
-    /// Handle mouse/keyboard input for radar
-    fn handle_radar_input(&mut self, ui: &mut egui::Ui, rect: egui::Rect) {
-        let response = ui.allocate_rect(rect, egui::Sense::click_and_drag());
+    /// Render the annotation popup opened by right-clicking a target or tag
+    fn render_annotation_editor(&mut self, ctx: &egui::Context) {
+        let Some((callsign, pos)) = self.ui_state.annotating.clone() else {
+            return;
+        };
+
+        let annotation = self.state.get_annotation(&callsign);
+        let mut edited = annotation.clone();
+        let mut open = true;
+        let mut done = false;
+
+        egui::Window
+            ::new(format!("{} annotations", callsign))
+            .id(egui::Id::new("annotation_editor"))
+            .current_pos(pos)
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    annotation_fields(ui, &mut edited);
+                });
+                ui.horizontal(|ui| {
+                    done = ui.button("Done").clicked();
+                    if ui.button("Clear").clicked() {
+                        edited = Default::default();
+                    }
+                });
+            });
 
-        // Panning with middle mouse button or drag
-        if
-            response.dragged_by(egui::PointerButton::Middle) ||
-            (response.dragged() && ui.input(|i| i.modifiers.shift))
-        {
-            let delta = response.drag_delta();
-            self.projection.pan(delta);
+        if edited != annotation {
+            self.state.set_annotation(&callsign, edited);
         }
 
-        // Zoom with scroll wheel
-        let scroll = ui.input(|i| i.smooth_scroll_delta.y);
-        if scroll != 0.0 {
-            let mouse_pos = response.hover_pos();
-            self.projection.zoom(scroll, mouse_pos);
+        if !open || done || ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.ui_state.annotating = None;
         }
+    }
 
-        // Click to select aircraft
-        if response.clicked() {
-            if let Some(pos) = response.interact_pointer_pos() {
-                self.select_aircraft_at_position(pos);
-            }
+    /// Render settings window
+    fn render_settings(&mut self, ui: &mut egui::Ui) {
+        // Preview tags with the selected aircraft, or any aircraft when none is selected
+        let aircraft = self.state.get_aircraft();
+        let tracked = self.ui_state.selected_aircraft
+            .as_ref()
+            .and_then(|callsign| aircraft.get(callsign))
+            .or_else(|| aircraft.values().min_by(|a, b| a.callsign.cmp(&b.callsign)));
+        let advisories = self.state.get_wake_advisories();
+        let annotation = tracked.map(|tracked| self.state.get_annotation(&tracked.callsign));
+        let preview = tracked.map(|tracked| TagContext {
+            tracked,
+            wake: advisories.get(&tracked.callsign),
+            annotation: annotation.as_ref(),
+            wake_mode: self.config.wake.mode,
+            magnetic_variation: self.config.display.magnetic_variation,
+        });
+
+        // Edits made with a profile active are saved as that profile's overrides
+        if let Some(name) = &self.config.active_profile {
+            let base = self.state.get_base_config();
+            ui.label(egui::RichText::new(profile_label(&base, name)).strong());
+            ui.weak(format!("Changes are saved to [profile.{}] in config.toml", name));
+            ui.separator();
+        }
+
+        let mut config = self.config.clone();
+        if self.ui_state.settings.show(ui, &mut config, &self.colors, preview.as_ref()) {
+            self.edit_config(|c| *c = config);
         }
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            let path = crate::config::config_path();
+            ui.label(format!("Config file: {}", path.display()));
+
+            if ui.button("Open Config Folder").clicked() {
+                let folder = path
+                    .parent()
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .unwrap_or(std::path::Path::new("."));
+
+                #[cfg(target_os = "windows")]
+                std::process::Command::new("explorer").arg(folder).spawn().ok();
+
+                #[cfg(target_os = "macos")]
+                std::process::Command::new("open").arg(folder).spawn().ok();
+
+                #[cfg(target_os = "linux")]
+                std::process::Command::new("xdg-open").arg(folder).spawn().ok();
+            }
+        });
     }
 
-    /// Select aircraft at screen position
-    fn select_aircraft_at_position(&mut self, screen_pos: egui::Pos2) {
-        let aircraft = self.state.get_aircraft();
-        let threshold = 15.0; // Click radius in pixels
+    /// Render main radar display
+    fn render_radar(&mut self, ui: &mut egui::Ui) {
+        let config = self.drawn_config();
+        let mut scope_ctx = ScopeContext {
+            state: &self.state,
+            config: &config,
+            ui_state: &mut self.ui_state,
+            time_millis: self.start_time.elapsed().as_millis() as i64,
+        };
+        self.scope.show(ui, &mut scope_ctx);
+    }
 
-        let mut closest: Option<(&String, f32)> = None;
+    /// Render open insets and extra windows
+    fn render_viewports(&mut self, ctx: &egui::Context) {
+        let time_millis = self.start_time.elapsed().as_millis() as i64;
+        let drawn = self.drawn_config();
+
+        for viewport in self.viewports.iter_mut().filter(|v| v.layout.open) {
+            let config = viewport.layout.apply(&drawn);
+            let mut scope_ctx = ScopeContext {
+                state: &self.state,
+                config: &config,
+                ui_state: &mut self.ui_state,
+                time_millis,
+            };
 
-        for (callsign, tracked) in &aircraft {
-            let aircraft_pos = self.projection.studs_to_screen(
-                tracked.info.position.x,
-                tracked.info.position.y
-            );
+            match viewport.layout.kind {
+                ViewportKind::Inset => viewport.show_inset(ctx, &mut scope_ctx),
+                ViewportKind::Window => viewport.show_window(ctx, &mut scope_ctx),
+            }
+        }
+    }
 
-            let distance = screen_pos.distance(aircraft_pos);
+    /// Config the scopes draw with, using the colors in use
+    fn drawn_config(&self) -> RadarConfig {
+        RadarConfig { colors: self.colors.clone(), ..self.config.clone() }
+    }
 
-            if distance < threshold {
-                if let Some((_, min_dist)) = closest {
-                    if distance < min_dist {
-                        closest = Some((callsign, distance));
-                    }
-                } else {
-                    closest = Some((callsign, distance));
+    /// Create, update and drop viewports to match the config
+    fn sync_viewports(&mut self) {
+        let mut viewports = Vec::with_capacity(self.config.viewports.len());
+
+        for layout in &self.config.viewports {
+            let viewport = match self.viewports.iter().position(|v| v.layout.name == layout.name) {
+                Some(index) => {
+                    let mut viewport = self.viewports.swap_remove(index);
+                    viewport.update_layout(layout);
+                    viewport
                 }
+                None => Viewport::new(layout.clone()),
+            };
+            viewports.push(viewport);
+        }
+
+        self.viewports = viewports;
+    }
+
+    /// Write moved, resized, panned or closed viewports back to the config
+    fn save_viewport_layout(&mut self) {
+        let mut changed = false;
+
+        for viewport in &mut self.viewports {
+            let Some(layout) = self.config.viewports
+                .iter_mut()
+                .find(|layout| layout.name == viewport.layout.name) else {
+                continue;
+            };
+
+            let current = viewport.current_layout();
+            if current.layout_differs(layout) {
+                *layout = current.clone();
+                viewport.layout = current;
+                changed = true;
             }
         }
 
-        if let Some((callsign, _)) = closest {
-            self.renderer.selected_aircraft = Some(callsign.clone());
-        } else {
-            self.renderer.selected_aircraft = None;
+        if changed {
+            self.state.update_config(self.config.clone());
+            self.ui_state.config_dirty = true;
         }
     }
 
-    /// Draw center crosshair
-    fn draw_center_crosshair(&self, ui: &egui::Ui, rect: egui::Rect) {
-        let center = rect.center();
-        let size = 10.0;
-        let color = egui::Color32::from_rgba_unmultiplied(255, 255, 255, 100);
+    /// Keyboard shortcuts from the `[keybindings]` config
+    fn handle_keyboard(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
 
-        ui.painter().line_segment(
-            [
-                egui::Pos2::new(center.x - size, center.y),
-                egui::Pos2::new(center.x + size, center.y),
-            ],
-            egui::Stroke::new(1.0, color)
-        );
+        let shortcuts = self.config.keybindings.shortcuts();
+        let actions: Vec<Action> = ctx.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| {
+                    let egui::Event::Key { key, pressed: true, repeat, modifiers, .. } = event else {
+                        return None;
+                    };
+                    shortcuts
+                        .iter()
+                        .find(|(action, shortcut)| {
+                            shortcut.logical_key == *key &&
+                                modifiers.matches_exact(shortcut.modifiers) &&
+                                (!repeat || action.repeats())
+                        })
+                        .map(|(action, _)| *action)
+                })
+                .collect()
+        });
 
-        ui.painter().line_segment(
-            [
-                egui::Pos2::new(center.x, center.y - size),
-                egui::Pos2::new(center.x, center.y + size),
-            ],
-            egui::Stroke::new(1.0, color)
-        );
+        for action in actions {
+            self.run_action(action, ctx);
+        }
     }
+
+    fn run_action(&mut self, action: Action, ctx: &egui::Context) {
+        let pan_step = self.scope.projection.screen_height * KEY_PAN_FRACTION;
+
+        match action {
+            Action::ToggleSidebar => self.ui_state.show_sidebar = !self.ui_state.show_sidebar,
+            Action::ToggleStrips => self.ui_state.show_strips = !self.ui_state.show_strips,
+            Action::ToggleSettings => self.ui_state.show_settings = !self.ui_state.show_settings,
+            Action::ToggleVectors => self.edit_config(|c| c.display.show_vectors = !c.display.show_vectors),
+            Action::ToggleHistory => self.edit_config(|c| c.display.show_history = !c.display.show_history),
+            Action::ToggleTags => self.edit_config(|c| c.display.show_tags = !c.display.show_tags),
+            Action::ToggleCompassRose => {
+                self.edit_config(|c| c.display.show_compass_rose = !c.display.show_compass_rose);
+            }
+            Action::ToggleAltitudeFilter => {
+                self.edit_config(|c| c.filters.altitude_enabled = !c.filters.altitude_enabled);
+            }
+            Action::SelectNext => self.cycle_selection(true),
+            Action::SelectPrevious => self.cycle_selection(false),
+            Action::Cancel => {
+                if self.ui_state.measure.take().is_none() {
+                    self.ui_state.selected_aircraft = None;
+                }
+            }
+            Action::ZoomIn => self.scope.projection.zoom(1.0, None),
+            Action::ZoomOut => self.scope.projection.zoom(-1.0, None),
+            Action::PanUp => self.pan(egui::vec2(0.0, pan_step)),
+            Action::PanDown => self.pan(egui::vec2(0.0, -pan_step)),
+            Action::PanLeft => self.pan(egui::vec2(pan_step, 0.0)),
+            Action::PanRight => self.pan(egui::vec2(-pan_step, 0.0)),
+            Action::RecallView1 |
+            Action::RecallView2 |
+            Action::RecallView3 |
+            Action::RecallView4 |
+            Action::RecallView5 |
+            Action::RecallView6 |
+            Action::RecallView7 |
+            Action::RecallView8 |
+            Action::RecallView9 => {
+                let preset = action
+                    .view_index()
+                    .and_then(|index| self.config.views.presets.get(index).cloned());
+                if let Some(preset) = preset {
+                    self.recall_view(&preset);
+                }
+            }
+            Action::AcknowledgeAlerts => self.state.acknowledge_wake_alerts(),
+            Action::Follow => self.toggle_follow(),
+            Action::Measure => {
+                self.toggle_measure();
+            }
+            Action::CommandLine => {
+                self.ui_state.command_input = ".".to_string();
+                self.ui_state.command_cursor_to_end = true;
+                ctx.memory_mut(|m| m.request_focus(egui::Id::new(COMMAND_LINE_ID)));
+            }
+            Action::AssumeRelease | Action::RotateTag | Action::ResetTag => {
+                if let Some(callsign) = self.ui_state.selected_aircraft.clone() {
+                    self.run_selection_action(action, &callsign);
+                }
+            }
+        }
+    }
+
+    /// Actions on the selected aircraft
+    fn run_selection_action(&mut self, action: Action, callsign: &str) {
+        match action {
+            Action::AssumeRelease => self.state.toggle_tracked(callsign),
+            Action::ResetTag => self.state.clear_tag_offset(callsign),
+            Action::RotateTag => {
+                let gap = self.config.data_tags.placement_gap;
+                let offset = std::iter
+                    ::once(&self.scope)
+                    .chain(self.viewports.iter().map(|v| &v.scope))
+                    .find_map(|scope| scope.renderer.rotated_tag_offset(callsign, gap));
+
+                if let Some(offset) = offset {
+                    self.state.set_tag_offset(callsign, offset);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Save UI config changes, into the active profile when there is one
+    fn persist_config(&mut self) {
+        self.ui_state.config_dirty = false;
+
+        let base = match profiles::store_edits(&self.state.get_base_config(), &self.config) {
+            Ok(base) => base,
+            Err(e) => {
+                tracing::error!("Failed to store config changes in profile: {}", e);
+                return;
+            }
+        };
+        // Invalid values stay in the editor rather than landing in config.toml
+        if let Err(e) = base.validate() {
+            tracing::warn!("Not saving config changes: {}", e);
+            self.ui_state.settings.set_save_error(Some(e));
+            return;
+        }
+        self.ui_state.settings.set_save_error(None);
+
+        if let Err(e) = crate::config::save_config(&base) {
+            tracing::error!("Failed to save config: {}", e);
+        }
+        if let Err(e) = self.state.load_base_config(base) {
+            tracing::error!("Failed to apply config profile: {}", e);
+        }
+    }
+
+    /// Switch to a config profile, or back to the base config with `None`
+    fn switch_profile(&mut self, name: Option<String>) -> Result<(), String> {
+        if self.state.get_config_error().is_some() {
+            return Err("Fix the problems in config.toml before switching profile".to_string());
+        }
+
+        // Unsaved edits belong to the profile they were made in
+        if self.ui_state.config_dirty {
+            self.persist_config();
+        }
+
+        let mut base = self.state.get_base_config();
+        if let Some(name) = &name && !base.profile.contains_key(name) {
+            return Err(format!("No profile called {}", name));
+        }
+        base.active_profile = name;
+
+        // An explicit switch replaces any profile chosen on the command line
+        self.state.set_profile_override(None);
+        self.state.load_base_config(base.clone()).map_err(|e| e.to_string())?;
+        self.config = self.state.get_config();
+        if let Err(e) = crate::config::save_config(&base) {
+            tracing::error!("Failed to save config: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Change the config from the UI, applying it now and saving it later
+    fn edit_config(&mut self, edit: impl FnOnce(&mut RadarConfig)) {
+        edit(&mut self.config);
+        self.state.update_config(self.config.clone());
+        self.ui_state.config_dirty = true;
+    }
+
+    /// Pan the main scope by a screen distance, leaving follow mode
+    fn pan(&mut self, delta: egui::Vec2) {
+        self.scope.follow = None;
+        self.scope.projection.pan(delta);
+    }
+
+    /// Select the next or previous aircraft shown on the main scope, by callsign
+    fn cycle_selection(&mut self, forward: bool) {
+        let mut callsigns: Vec<String> = self.state
+            .get_aircraft()
+            .into_values()
+            .filter(|tracked| self.config.filters.visibility(tracked, false) != Visibility::Hidden)
+            .map(|tracked| tracked.callsign)
+            .collect();
+        if callsigns.is_empty() {
+            return;
+        }
+        callsigns.sort();
+
+        let current = self.ui_state.selected_aircraft
+            .as_ref()
+            .and_then(|selected| callsigns.iter().position(|c| c == selected));
+        let index = match (current, forward) {
+            (Some(i), true) => (i + 1) % callsigns.len(),
+            (Some(i), false) => (i + callsigns.len() - 1) % callsigns.len(),
+            (None, true) => 0,
+            (None, false) => callsigns.len() - 1,
+        };
+
+        self.ui_state.selected_aircraft = Some(callsigns.swap_remove(index));
+    }
+
+    /// Start or stop the ruler, returning whether it is now active
+    fn toggle_measure(&mut self) -> bool {
+        if self.ui_state.measure.take().is_some() {
+            false
+        } else {
+            self.ui_state.measure = Some(Measure::default());
+            true
+        }
+    }
+
+    /// Render the command line with its last result
+    fn render_command_line(&mut self, ui: &mut egui::Ui) {
+        let id = egui::Id::new(COMMAND_LINE_ID);
+
+        if ui.memory(|m| m.has_focus(id)) {
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+                self.complete_command();
+            }
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp)) {
+                self.browse_history(true);
+            }
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)) {
+                self.browse_history(false);
+            }
+        }
+
+        if std::mem::take(&mut self.ui_state.command_cursor_to_end) {
+            let mut state = egui::TextEdit::load_state(ui.ctx(), id).unwrap_or_default();
+            let end = egui::text::CCursor::new(self.ui_state.command_input.chars().count());
+            state.cursor.set_char_range(Some(egui::text::CCursorRange::one(end)));
+            state.store(ui.ctx(), id);
+        }
+
+        ui.horizontal(|ui| {
+            ui.monospace(">");
+
+            let response = ui.add(
+                egui::TextEdit
+                    ::singleline(&mut self.ui_state.command_input)
+                    .id(id)
+                    .font(egui::TextStyle::Monospace)
+                    .hint_text("Press . for commands, .help for a list")
+                    .lock_focus(true)
+                    .desired_width(360.0)
+            );
+
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let input = std::mem::take(&mut self.ui_state.command_input);
+                if !input.trim().is_empty() {
+                    self.run_command(&input);
+                    self.ui_state.command_history.push(input);
+                    self.ui_state.history_index = None;
+                    response.request_focus();
+                }
+            }
+
+            match &self.ui_state.command_feedback {
+                Some(Ok(message)) => {
+                    ui.weak(message);
+                }
+                Some(Err(error)) => {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+                }
+                None => {}
+            }
+        });
+    }
+
+    /// Step through earlier commands
+    fn browse_history(&mut self, older: bool) {
+        let history = &self.ui_state.command_history;
+        if history.is_empty() {
+            return;
+        }
+
+        let index = match (self.ui_state.history_index, older) {
+            (None, true) => Some(history.len() - 1),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) => (index + 1 < history.len()).then_some(index + 1),
+        };
+
+        self.ui_state.command_input = index.map(|i| history[i].clone()).unwrap_or_default();
+        self.ui_state.history_index = index;
+        self.ui_state.command_cursor_to_end = true;
+    }
+
+    /// Tab-complete the command name, callsign or airport being typed
+    fn complete_command(&mut self) {
+        let mut callsigns: Vec<String> = self.state.get_aircraft().into_keys().collect();
+        callsigns.extend(self.state.get_strips().into_keys());
+
+        let mut airports: Vec<String> = self.config.views.airports
+            .iter()
+            .map(|a| a.icao.to_uppercase())
+            .collect();
+        airports.extend(self.state.get_airport_fixes().into_keys());
+
+        let profiles: Vec<String> = self.state.get_base_config().profile.into_keys().chain(["base".to_string()]).collect();
+
+        if
+            let Some(completion) = commands::complete(
+                &self.ui_state.command_input,
+                &callsigns,
+                &airports,
+                &profiles
+            )
+        {
+            self.ui_state.command_input = completion.input;
+            self.ui_state.command_cursor_to_end = true;
+            if !completion.candidates.is_empty() {
+                self.ui_state.command_feedback = Some(Ok(completion.candidates.join("  ")));
+            }
+        }
+    }
+
+    /// Parse and run a command line entry, showing the result
+    fn run_command(&mut self, input: &str) {
+        let result = match commands::parse(input) {
+            Ok(command) => self.execute_command(command),
+            Err(e) => Err(e.to_string()),
+        };
+        self.ui_state.command_feedback = Some(result);
+    }
+
+    fn execute_command(&mut self, command: Command) -> Result<String, String> {
+        match command {
+            Command::Find(callsign) => {
+                let callsign = self.resolve_callsign(&callsign)?;
+                let aircraft = self.state
+                    .get_aircraft_by_callsign(&callsign)
+                    .ok_or_else(|| format!("{} is not on the feed", callsign))?;
+
+                self.scope.follow = None;
+                self.scope.projection.center = (aircraft.info.position.x, aircraft.info.position.y);
+                self.ui_state.selected_aircraft = Some(callsign.clone());
+                Ok(format!("Found {}", callsign))
+            }
+            Command::Center(place) => {
+                if self.snap_to_airport(&place) {
+                    return Ok(format!("Centred on {}", place));
+                }
+
+                let callsign = self
+                    .resolve_callsign(&place)
+                    .map_err(|_| format!("No airport or aircraft called {}", place))?;
+                let aircraft = self.state
+                    .get_aircraft_by_callsign(&callsign)
+                    .ok_or_else(|| format!("{} is not on the feed", callsign))?;
+
+                self.scope.follow = None;
+                self.scope.projection.center = (aircraft.info.position.x, aircraft.info.position.y);
+                Ok(format!("Centred on {}", callsign))
+            }
+            Command::ClearedLevel { callsign, level } => {
+                self.annotate(&callsign, |a| a.cleared_level = level).map(|callsign| match level {
+                    Some(level) => format!("{} cleared FL{:03}", callsign, level / 100),
+                    None => format!("{} cleared level removed", callsign),
+                })
+            }
+            Command::Heading { callsign, heading } => {
+                self.annotate(&callsign, |a| a.heading = heading).map(|callsign| match heading {
+                    Some(heading) => format!("{} heading {:03}", callsign, heading),
+                    None => format!("{} heading removed", callsign),
+                })
+            }
+            Command::Speed { callsign, speed } => {
+                self.annotate(&callsign, |a| a.speed = speed).map(|callsign| match speed {
+                    Some(speed) => format!("{} speed {}", callsign, speed),
+                    None => format!("{} speed removed", callsign),
+                })
+            }
+            Command::Runway { callsign, runway } => {
+                let message = format!("runway {}", runway);
+                self.annotate(&callsign, |a| a.runway = runway).map(|callsign| {
+                    format!("{} {}", callsign, message)
+                })
+            }
+            Command::Scratchpad { callsign, text } => {
+                self.annotate(&callsign, |a| a.scratchpad = text).map(|callsign| {
+                    format!("{} scratchpad updated", callsign)
+                })
+            }
+            Command::Measure => {
+                if self.toggle_measure() {
+                    Ok("Click two points to measure".to_string())
+                } else {
+                    Ok("Ruler off".to_string())
+                }
+            }
+            Command::Filter(filter) => {
+                let filters = &mut self.config.filters;
+                let message = match filter {
+                    FilterCommand::Altitude { floor, ceiling } => {
+                        filters.altitude_enabled = true;
+                        filters.altitude_floor = floor.min(ceiling);
+                        filters.altitude_ceiling = floor.max(ceiling);
+                        format!("Showing {:.0} to {:.0} ft", filters.altitude_floor, filters.altitude_ceiling)
+                    }
+                    FilterCommand::AltitudeOff => {
+                        filters.altitude_enabled = false;
+                        "Altitude filter off".to_string()
+                    }
+                    FilterCommand::Off => {
+                        filters.altitude_enabled = false;
+                        filters.areas.iter_mut().for_each(|area| area.enabled = false);
+                        "Filters off".to_string()
+                    }
+                };
+
+                self.state.update_config(self.config.clone());
+                self.ui_state.config_dirty = true;
+                Ok(message)
+            }
+            Command::View(ViewCommand::Save(name)) => {
+                self.save_view(&name);
+                Ok(format!("Saved view {}", name))
+            }
+            Command::View(ViewCommand::Recall(name)) => {
+                let preset = self.config.views
+                    .preset(&name)
+                    .cloned()
+                    .ok_or_else(|| format!("No view called {}", name))?;
+                self.recall_view(&preset);
+                Ok(format!("View {}", preset.name))
+            }
+            Command::View(ViewCommand::Delete(name)) => {
+                let presets = &mut self.config.views.presets;
+                let before = presets.len();
+                presets.retain(|p| !p.name.eq_ignore_ascii_case(&name));
+                if presets.len() == before {
+                    return Err(format!("No view called {}", name));
+                }
+
+                self.state.update_config(self.config.clone());
+                self.ui_state.config_dirty = true;
+                Ok(format!("Deleted view {}", name))
+            }
+            Command::Zoom(zoom) => {
+                self.scope.projection.studs_per_pixel = zoom.clamp(1.0, 1000.0);
+                Ok(format!("Zoom {:.0} studs/px", self.scope.projection.studs_per_pixel))
+            }
+            Command::Profile(name) => {
+                self.switch_profile(name.clone())?;
+                Ok(match name {
+                    Some(name) => format!("Profile {}", name),
+                    None => "Base config".to_string(),
+                })
+            }
+            Command::Help => {
+                let names: Vec<String> = commands::COMMANDS
+                    .iter()
+                    .map(|(name, ..)| format!(".{}", name))
+                    .collect();
+                Ok(names.join("  "))
+            }
+        }
+    }
+
+    /// Apply a change to an aircraft's annotation, returning its full callsign
+    fn annotate(&mut self, callsign: &str, change: impl FnOnce(&mut Annotation)) -> Result<String, String> {
+        let callsign = self.resolve_callsign(callsign)?;
+        let mut annotation = self.state.get_annotation(&callsign);
+        change(&mut annotation);
+        self.state.set_annotation(&callsign, annotation);
+        Ok(callsign)
+    }
+
+    /// Match typed text to a known callsign, exactly or by unique prefix
+    fn resolve_callsign(&self, input: &str) -> Result<String, String> {
+        let mut known: Vec<String> = self.state.get_aircraft().into_keys().collect();
+        known.extend(self.state.get_strips().into_keys());
+        known.extend(self.state.get_annotations().into_keys());
+        known.sort();
+        known.dedup();
+
+        if let Some(exact) = known.iter().find(|c| c.eq_ignore_ascii_case(input)) {
+            return Ok(exact.clone());
+        }
+
+        let input = input.to_uppercase();
+        let matches: Vec<&String> = known
+            .iter()
+            .filter(|c| c.to_uppercase().starts_with(&input))
+            .collect();
+
+        match matches.as_slice() {
+            [only] => Ok((*only).clone()),
+            [] => Err(format!("No aircraft called {}", input)),
+            _ => Err(format!(
+                "{} is ambiguous: {}",
+                input,
+                matches.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+
+    /// Remember the main camera once it has stopped moving
+    fn save_last_view(&mut self) {
+        if !self.config.views.restore_last {
+            return;
+        }
+
+        let camera = CameraView::from_projection(&self.scope.projection);
+        match self.ui_state.last_camera {
+            Some((last, since)) if last == camera => {
+                if
+                    since.elapsed() >= LAST_VIEW_SAVE_DELAY &&
+                    self.ui_state.saved_camera != Some(camera)
+                {
+                    self.ui_state.saved_camera = Some(camera);
+                    if let Err(e) = crate::views::save_last_view(&camera) {
+                        tracing::error!("Failed to save last view: {}", e);
+                    }
+                }
+            }
+            _ => {
+                self.ui_state.last_camera = Some((camera, Instant::now()));
+            }
+        }
+    }
+
+    /// Move the main scope to a preset and apply its filters
+    fn recall_view(&mut self, preset: &ViewPreset) {
+        self.scope.follow = None;
+        preset.camera.apply_to(&mut self.scope.projection);
+
+        if let Some(filters) = &preset.filters {
+            self.config.filters = filters.clone();
+            self.state.update_config(self.config.clone());
+            self.ui_state.config_dirty = true;
+        }
+    }
+
+    /// Centre the main scope on an airport, returns false if its position is unknown
+    fn snap_to_airport(&mut self, icao: &str) -> bool {
+        let learned = self.state.get_airport_fixes();
+        match self.config.views.airport(icao, &learned) {
+            Some(mut camera) => {
+                self.scope.follow = None;
+                camera.rotation = self.scope.projection.rotation;
+                camera.apply_to(&mut self.scope.projection);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Render the view preset dropdown and airport list
+    fn render_view_controls(&mut self, ui: &mut egui::Ui) {
+        let mut views = self.config.views.clone();
+        let mut changed = false;
+        let mut recall = None;
+        let mut snap = None;
+        let mut save = None;
+
+        ui.menu_button("Views", |ui| {
+            let mut remove = None;
+
+            for (index, preset) in views.presets.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let key = Action::recall_view(index).and_then(|a| self.config.keybindings.label(a));
+                    let label = match key {
+                        Some(key) => format!("{}  ({})", preset.name, key),
+                        None => preset.name.clone(),
+                    };
+                    if ui.button(label).clicked() {
+                        recall = Some(preset.clone());
+                        ui.close_menu();
+                    }
+                    if ui.small_button("🗑").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+
+            if let Some(index) = remove {
+                views.presets.remove(index);
+                changed = true;
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.ui_state.new_view_name);
+
+                let name = self.ui_state.new_view_name.trim().to_string();
+                if ui.add_enabled(!name.is_empty(), egui::Button::new("Save view")).clicked() {
+                    save = Some(name);
+                    self.ui_state.new_view_name.clear();
+                }
+            });
+
+            // Configured airports plus any learned from traffic
+            let mut airports: Vec<String> = views.airports
+                .iter()
+                .map(|a| a.icao.to_uppercase())
+                .chain(self.state.get_airport_fixes().into_keys())
+                .collect();
+            airports.sort();
+            airports.dedup();
+
+            if !airports.is_empty() {
+                ui.separator();
+                ui.menu_button("Airports", |ui| {
+                    for icao in airports {
+                        if ui.button(&icao).clicked() {
+                            snap = Some(icao);
+                            ui.close_menu();
+                        }
+                    }
+                });
+            }
+        });
+
+        if let Some(preset) = recall {
+            self.recall_view(&preset);
+        }
+
+        if let Some(icao) = snap {
+            self.snap_to_airport(&icao);
+        }
+
+        if changed {
+            self.config.views = views;
+            self.state.update_config(self.config.clone());
+            self.ui_state.config_dirty = true;
+        }
+
+        if let Some(name) = save {
+            self.save_view(&name);
+        }
+    }
+
+    /// Render the profile menu, showing what the active profile overrides
+    fn render_profile_controls(&mut self, ui: &mut egui::Ui) {
+        let base = self.state.get_base_config();
+        if base.profile.is_empty() {
+            return;
+        }
+
+        let active = self.config.active_profile.clone();
+        let mut switch = None;
+
+        ui.separator();
+        ui.menu_button(format!("Profile: {}", active.as_deref().unwrap_or("base")), |ui| {
+            if ui.selectable_label(active.is_none(), "Base config").clicked() {
+                switch = Some(None);
+                ui.close_menu();
+            }
+            for name in base.profile.keys() {
+                if ui.selectable_label(active.as_ref() == Some(name), profile_label(&base, name)).clicked() {
+                    switch = Some(Some(name.clone()));
+                    ui.close_menu();
+                }
+            }
+
+            if let Some(name) = &active {
+                ui.separator();
+                let keys = profiles::overridden_keys(&base, name);
+                if keys.is_empty() {
+                    ui.weak(format!("{} overrides nothing yet", name));
+                } else {
+                    ui.weak(format!("{} overrides:", name));
+                    for key in keys {
+                        ui.monospace(key);
+                    }
+                }
+            }
+        });
+
+        if
+            let Some(name) = switch &&
+            let Err(e) = self.switch_profile(name)
+        {
+            self.ui_state.command_feedback = Some(Err(e));
+        }
+    }
+
+    /// Save the main camera and filters as a preset, replacing one with the same name
+    fn save_view(&mut self, name: &str) {
+        let preset = ViewPreset {
+            name: name.to_string(),
+            camera: CameraView::from_projection(&self.scope.projection),
+            filters: Some(self.config.filters.clone()),
+        };
+
+        let presets = &mut self.config.views.presets;
+        match presets.iter_mut().find(|p| p.name.eq_ignore_ascii_case(name)) {
+            Some(existing) => {
+                *existing = preset;
+            }
+            None => presets.push(preset),
+        }
+
+        self.state.update_config(self.config.clone());
+        self.ui_state.config_dirty = true;
+    }
+
+    /// Render the viewport list with open/close, add and remove
+    fn render_viewport_controls(&mut self, ui: &mut egui::Ui) {
+        let mut viewports = self.config.viewports.clone();
+        let mut changed = false;
+
+        ui.menu_button("Viewports", |ui| {
+            let mut remove = None;
+
+            for (index, viewport) in viewports.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    changed |= ui.checkbox(&mut viewport.open, &viewport.name).changed();
+
+                    let mut own_filters = viewport.filters.is_some();
+                    if ui.checkbox(&mut own_filters, "Own filters").changed() {
+                        viewport.filters = own_filters.then(|| self.config.filters.clone());
+                        changed = true;
+                    }
+
+                    if ui.small_button("🗑").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+
+            if let Some(index) = remove {
+                viewports.remove(index);
+                changed = true;
+            }
+
+            if !viewports.is_empty() {
+                ui.separator();
+            }
+
+            ui.horizontal(|ui| {
+                for (kind, label) in [(ViewportKind::Inset, "Inset"), (ViewportKind::Window, "Window")] {
+                    if ui.button(format!("New {}", label.to_lowercase())).clicked() {
+                        let name = (1..)
+                            .map(|n| format!("{} {}", label, n))
+                            .find(|name| viewports.iter().all(|v| &v.name != name))
+                            .unwrap_or_default();
+
+                        // Start zoomed in on whatever the main scope is looking at
+                        let projection = &self.scope.projection;
+                        let mut layout = ViewportConfig::new(
+                            name,
+                            kind,
+                            projection.center,
+                            (projection.studs_per_pixel / 4.0).max(1.0)
+                        );
+                        layout.rotation = projection.rotation;
+                        viewports.push(layout);
+                        changed = true;
+                    }
+                }
+            });
+        });
+
+        if changed {
+            self.config.viewports = viewports;
+            self.state.update_config(self.config.clone());
+            self.ui_state.config_dirty = true;
+        }
+    }
+}
+
+/// Camera and renderer for one scope
+struct Scope {
+    /// Coordinate projection
+    projection: Projection,
+
+    /// Radar renderer
+    renderer: RadarRenderer,
+
+    /// Aircraft kept centred, cleared by panning
+    follow: Option<Follow>,
+
+    /// Pointer position over the scope this frame
+    pointer: Option<egui::Pos2>,
+}
+
+/// Aircraft a scope keeps centred
+struct Follow {
+    callsign: String,
+
+    /// Rotate the scope so the aircraft's heading points up
+    track_up: bool,
+}
+
+/// Shared data a scope draws from and updates
+struct ScopeContext<'a> {
+    state: &'a RadarState,
+
+    /// Config with any viewport overrides applied
+    config: &'a RadarConfig,
+
+    ui_state: &'a mut UiState,
+
+    /// Milliseconds since startup, for animations
+    time_millis: i64,
+}
+
+impl Scope {
+    fn new(projection: Projection) -> Self {
+        Self {
+            projection,
+            renderer: RadarRenderer::new(),
+            follow: None,
+            pointer: None,
+        }
+    }
+
+    /// Rotate the scope by hand, which turns off track-up
+    fn rotate_manually(&mut self, degrees: f64) {
+        self.projection.set_rotation(degrees);
+        if let Some(follow) = &mut self.follow {
+            follow.track_up = false;
+        }
+    }
+
+    /// Start following an aircraft
+    fn start_follow(&mut self, callsign: String, track_up: bool) {
+        self.follow = Some(Follow { callsign, track_up });
+    }
+
+    /// Ease the camera towards the followed aircraft
+    fn update_follow(
+        &mut self,
+        aircraft: &HashMap<String, TrackedAircraft>,
+        dt: f32,
+        easing_secs: f32
+    ) {
+        let Some(follow) = &self.follow else {
+            return;
+        };
+        let Some(tracked) = aircraft.get(&follow.callsign) else {
+            // Dropped off the feed
+            self.follow = None;
+            return;
+        };
+
+        // Exponential smoothing, frame rate independent
+        let blend = if easing_secs > 0.0 { 1.0 - (-(dt / easing_secs) as f64).exp() } else { 1.0 };
+
+        let center = &mut self.projection.center;
+        center.0 += (tracked.info.position.x - center.0) * blend;
+        center.1 += (tracked.info.position.y - center.1) * blend;
+
+        if follow.track_up {
+            let rotation = self.projection.rotation;
+            let turn = (tracked.info.heading - rotation + 540.0).rem_euclid(360.0) - 180.0;
+            self.projection.set_rotation(rotation + turn * blend);
+        }
+    }
+
+    /// Draw the scope into the remaining space of `ui` and handle its input
+    fn show(&mut self, ui: &mut egui::Ui, ctx: &mut ScopeContext) {
+        let rect = ui.available_rect_before_wrap();
+
+        // Update projection screen area
+        self.projection.update_screen_rect(rect);
+
+        // Draw background
+        let bg_color = ctx.config.colors.color(&ctx.config.colors.background);
+        ui.painter().rect_filled(rect, 0.0, bg_color);
+
+        // Keep the followed aircraft centred
+        let aircraft = ctx.state.get_aircraft();
+        let dt = ui.input(|i| i.stable_dt).min(0.1);
+        self.update_follow(&aircraft, dt, ctx.config.display.camera_easing_secs);
+
+        // Handle input
+        self.handle_input(ui, rect, &aircraft, ctx);
+
+        // Get current aircraft
+        let tag_offsets = ctx.state.get_tag_offsets();
+        let tracked_by_me = ctx.state.get_tracked();
+        let wake_advisories = ctx.state.get_wake_advisories();
+        let annotations = ctx.state.get_annotations();
+        let scene = Scene {
+            aircraft: &aircraft,
+            tag_offsets: &tag_offsets,
+            tracked_by_me: &tracked_by_me,
+            wake_advisories: &wake_advisories,
+            annotations: &annotations,
+        };
+        self.renderer.search_filter = ctx.ui_state.search_filter.clone();
+        self.renderer.selected_aircraft = ctx.ui_state.selected_aircraft.clone();
+
+        // Render radar
+        self.renderer.render(
+            &ui.painter_at(rect),
+            &self.projection,
+            &scene,
+            ctx.config,
+            ctx.time_millis
+        );
+
+        // Range and bearing ruler
+        self.draw_measure(&ui.painter_at(rect), ctx);
+
+        // Draw center crosshair
+        Self::draw_center_crosshair(ui, rect);
+    }
+
+    /// Draw the ruler from its start to its end, or to the pointer while placing it
+    fn draw_measure(&self, painter: &egui::Painter, ctx: &ScopeContext) {
+        let Some(measure) = ctx.ui_state.measure else {
+            return;
+        };
+        let Some(start) = measure.start else {
+            return;
+        };
+        let end = measure.end.or_else(|| self.pointer.map(|pos| self.projection.screen_to_studs(pos)));
+        let Some(end) = end else {
+            return;
+        };
+
+        let color = ctx.config.colors.color(&ctx.config.colors.target_selected);
+        let from = self.projection.studs_to_screen(start.0, start.1);
+        let to = self.projection.studs_to_screen(end.0, end.1);
+        painter.line_segment([from, to], egui::Stroke::new(1.5, color));
+
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let distance = dx.hypot(dy) / STUDS_PER_NM;
+        let bearing = magnetic_bearing(
+            dx.atan2(-dy).to_degrees(), // -y is North
+            ctx.config.display.magnetic_variation
+        );
+
+        painter.text(
+            to + egui::vec2(8.0, -8.0),
+            egui::Align2::LEFT_BOTTOM,
+            format!("{:.1}NM {:03.0}°", distance, bearing),
+            egui::FontId::monospace(ctx.config.display.font_size),
+            color
+        );
+    }
+
+    /// Handle mouse input for the scope
+    fn handle_input(
+        &mut self,
+        ui: &mut egui::Ui,
+        rect: egui::Rect,
+        aircraft: &HashMap<String, TrackedAircraft>,
+        ctx: &mut ScopeContext
+    ) {
+        let response = ui.allocate_rect(rect, egui::Sense::click_and_drag());
+        let shift = ui.input(|i| i.modifiers.shift);
+
+        // Start dragging a data tag if the drag began on one
+        if response.drag_started_by(egui::PointerButton::Primary) && !shift {
+            let origin = ui.input(|i| i.pointer.press_origin());
+            ctx.ui_state.dragging_tag = origin.and_then(|pos| self.renderer.tag_at(pos));
+        }
+
+        if let Some(callsign) = &ctx.ui_state.dragging_tag {
+            if response.dragged_by(egui::PointerButton::Primary) {
+                let offset = ctx.state
+                    .get_tag_offsets()
+                    .get(callsign)
+                    .copied()
+                    .or_else(|| self.renderer.tag_offset(callsign));
+
+                if let Some((x, y)) = offset {
+                    let delta = response.drag_delta();
+                    ctx.state.set_tag_offset(callsign, (x + delta.x, y + delta.y));
+                }
+            }
+
+            if response.drag_stopped() {
+                ctx.ui_state.dragging_tag = None;
+            }
+        }
+
+        // Hovering a target or tag temporarily expands the tag
+        self.renderer.hovered_aircraft = response
+            .hover_pos()
+            .and_then(|pos| {
+                self.renderer.tag_at(pos).or_else(|| self.aircraft_at_position(pos, aircraft))
+            });
+
+        // Panning with middle mouse button or drag
+        if
+            response.dragged_by(egui::PointerButton::Middle) ||
+            (response.dragged() && shift && ctx.ui_state.dragging_tag.is_none())
+        {
+            let delta = response.drag_delta();
+            self.projection.pan(delta);
+
+            // Manual panning ends follow mode
+            self.follow = None;
+        }
+
+        // Zoom with scroll wheel
+        let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+        if scroll != 0.0 && response.hovered() {
+            let mouse_pos = response.hover_pos();
+            self.projection.zoom(scroll, mouse_pos);
+        }
+
+        self.pointer = response.hover_pos();
+
+        // Click to place the ruler, or to select aircraft
+        if response.clicked() && let Some(pos) = response.interact_pointer_pos() {
+            if let Some(measure) = &mut ctx.ui_state.measure {
+                let point = Some(self.projection.screen_to_studs(pos));
+                match measure.start {
+                    Some(_) if measure.end.is_none() => measure.end = point,
+                    _ => *measure = Measure { start: point, end: None },
+                }
+            } else {
+                ctx.ui_state.selected_aircraft = self.select_aircraft_at_position(pos, aircraft);
+            }
+        }
+
+        // Right-click a target or tag to edit its annotation
+        if
+            response.secondary_clicked() &&
+            let Some(pos) = response.interact_pointer_pos() &&
+            let Some(callsign) = self.select_aircraft_at_position(pos, aircraft)
+        {
+            ctx.ui_state.annotating = Some((callsign, pos));
+        }
+    }
+
+    /// Aircraft at screen position (by its target or its data tag)
+    fn select_aircraft_at_position(
+        &self,
+        screen_pos: egui::Pos2,
+        aircraft: &HashMap<String, TrackedAircraft>
+    ) -> Option<String> {
+        self.renderer
+            .tag_at(screen_pos)
+            .or_else(|| self.aircraft_at_position(screen_pos, aircraft))
+    }
+
+    /// Find the aircraft target closest to a screen position, ignoring targets hidden by filters
+    fn aircraft_at_position(
+        &self,
+        screen_pos: egui::Pos2,
+        aircraft: &HashMap<String, TrackedAircraft>
+    ) -> Option<String> {
+        let threshold = 15.0; // Click radius in pixels
+
+        let mut closest: Option<(&String, f32)> = None;
+
+        for (callsign, tracked) in aircraft {
+            if self.renderer.visibility_of(callsign) == Visibility::Hidden {
+                continue;
+            }
+
+            let aircraft_pos = self.projection.studs_to_screen(
+                tracked.info.position.x,
+                tracked.info.position.y
+            );
+
+            let distance = screen_pos.distance(aircraft_pos);
+
+            if distance < threshold {
+                if let Some((_, min_dist)) = closest {
+                    if distance < min_dist {
+                        closest = Some((callsign, distance));
+                    }
+                } else {
+                    closest = Some((callsign, distance));
+                }
+            }
+        }
+
+        closest.map(|(callsign, _)| callsign.clone())
+    }
+
+    /// Draw center crosshair
+    fn draw_center_crosshair(ui: &egui::Ui, rect: egui::Rect) {
+        let center = rect.center();
+        let size = 10.0;
+        let color = egui::Color32::from_rgba_unmultiplied(255, 255, 255, 100);
+
+        ui.painter().line_segment(
+            [
+                egui::Pos2::new(center.x - size, center.y),
+                egui::Pos2::new(center.x + size, center.y),
+            ],
+            egui::Stroke::new(1.0, color)
+        );
+
+        ui.painter().line_segment(
+            [
+                egui::Pos2::new(center.x, center.y - size),
+                egui::Pos2::new(center.x, center.y + size),
+            ],
+            egui::Stroke::new(1.0, color)
+        );
+    }
+}
+
+/// Extra scope shown as an inset or in its own window
+struct Viewport {
+    /// Layout as last loaded or saved
+    layout: ViewportConfig,
+
+    scope: Scope,
+
+    /// Where the viewport was last drawn (position, size)
+    placement: Option<(egui::Pos2, egui::Vec2)>,
+
+    /// Window settings, fixed at creation so the OS window isn't fought over
+    builder: egui::ViewportBuilder,
+}
+
+impl Viewport {
+    fn new(layout: ViewportConfig) -> Self {
+        let mut builder = egui::ViewportBuilder
+            ::default()
+            .with_title(layout.name.clone())
+            .with_inner_size(layout.size);
+        if let Some(position) = layout.position {
+            builder = builder.with_position(position);
+        }
+
+        let mut projection = Projection::new(layout.size.0, layout.size.1);
+        projection.center = layout.center;
+        projection.studs_per_pixel = layout.zoom;
+        projection.set_rotation(layout.rotation);
+
+        Self {
+            layout,
+            scope: Scope::new(projection),
+            placement: None,
+            builder,
+        }
+    }
+
+    /// Pick up changes made to the layout in the config
+    fn update_layout(&mut self, layout: &ViewportConfig) {
+        if layout.layout_differs(&self.layout) {
+            self.scope.projection.center = layout.center;
+            self.scope.projection.studs_per_pixel = layout.zoom;
+            self.scope.projection.set_rotation(layout.rotation);
+        }
+        self.layout = layout.clone();
+    }
+
+    /// Layout with the current camera and placement
+    fn current_layout(&self) -> ViewportConfig {
+        let mut layout = self.layout.clone();
+        layout.center = self.scope.projection.center;
+        layout.zoom = self.scope.projection.studs_per_pixel;
+        layout.rotation = self.scope.projection.rotation;
+
+        if let Some((position, size)) = self.placement {
+            layout.position = Some((position.x.round(), position.y.round()));
+            layout.size = (size.x.round(), size.y.round());
+        }
+
+        layout
+    }
+
+    /// Draw as a floating inset over the main window
+    fn show_inset(&mut self, ctx: &egui::Context, scope_ctx: &mut ScopeContext) {
+        let mut open = true;
+        let mut window = egui::Window
+            ::new(self.layout.name.clone())
+            .id(egui::Id::new(("viewport", &self.layout.name)))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_size(self.layout.size)
+            .frame(egui::Frame::window(&ctx.style()).inner_margin(2.0));
+        if let Some(position) = self.layout.position {
+            window = window.default_pos(position);
+        }
+
+        let shown = window.show(ctx, |ui| {
+            self.scope.show(ui, scope_ctx);
+        });
+
+        if let Some(shown) = shown {
+            let size = egui::vec2(self.scope.projection.screen_width, self.scope.projection.screen_height);
+            self.placement = Some((shown.response.rect.min, size));
+        }
+
+        if !open {
+            self.layout.open = false;
+        }
+    }
+
+    /// Draw in a separate native window
+    fn show_window(&mut self, ctx: &egui::Context, scope_ctx: &mut ScopeContext) {
+        let id = egui::ViewportId::from_hash_of(("viewport", &self.layout.name));
+
+        ctx.show_viewport_immediate(id, self.builder.clone(), |ctx, class| {
+            if class == egui::ViewportClass::Embedded {
+                // Backend can't open more windows, show it as an inset instead
+                self.show_inset(ctx, scope_ctx);
+                return;
+            }
+
+            egui::CentralPanel
+                ::default()
+                .frame(egui::Frame::none())
+                .show(ctx, |ui| {
+                    self.scope.show(ui, scope_ctx);
+                });
+
+            let (outer, inner, close) = ctx.input(|i| {
+                let info = i.viewport();
+                (info.outer_rect, info.inner_rect, info.close_requested())
+            });
+            if let (Some(outer), Some(inner)) = (outer, inner) {
+                self.placement = Some((outer.min, inner.size()));
+            }
+
+            if close {
+                self.layout.open = false;
+            }
+        });
+    }
+}
+
+/// Profile name with the profiles it inherits from, ending at the base config
+fn profile_label(base: &RadarConfig, name: &str) -> String {
+    let chain = profiles::chain(base, name).unwrap_or_default();
+    let parents: Vec<&str> = chain.into_iter().skip(1).chain(["base"]).collect();
+    format!("{} (inherits {})", name, parents.join(" → "))
+}
+
+/// Inline fields for an aircraft's annotation
+fn annotation_fields(ui: &mut egui::Ui, annotation: &mut Annotation) {
+    // Checked like the matching commands; cleared level is entered in hundreds of feet
+    ui.small("CFL");
+    number_field(
+        ui,
+        &mut annotation.cleared_level,
+        32.0,
+        |level| (level / 100).to_string(),
+        |text| commands::level(text).ok()
+    );
+    ui.small("HDG");
+    number_field(ui, &mut annotation.heading, 32.0, |heading| heading.to_string(), |text| {
+        commands::heading(text).ok()
+    });
+    ui.small("SPD");
+    number_field(ui, &mut annotation.speed, 32.0, |speed| speed.to_string(), |text| text.parse().ok());
+    ui.small("RWY");
+    ui.add(egui::TextEdit::singleline(&mut annotation.runway).desired_width(32.0));
+    ui.add(
+        egui::TextEdit::singleline(&mut annotation.scratchpad).hint_text("Scratchpad").desired_width(96.0)
+    );
+}
+
+/// Text field editing an optional number, empty means unset and invalid text is ignored
+fn number_field<T: Copy>(
+    ui: &mut egui::Ui,
+    value: &mut Option<T>,
+    width: f32,
+    show: impl Fn(T) -> String,
+    parse: impl Fn(&str) -> Option<T>
+) -> egui::Response {
+    let mut text = value.map(show).unwrap_or_default();
+    let response = ui.add(egui::TextEdit::singleline(&mut text).desired_width(width));
+
+    if response.changed() {
+        let text = text.trim();
+        if text.is_empty() {
+            *value = None;
+        } else if let Some(parsed) = parse(text) {
+            *value = Some(parsed);
+        }
+    }
+
+    response
 }
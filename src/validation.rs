@@ -0,0 +1,275 @@
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use thiserror::Error;
+use toml_edit::{ImDocument, Item, Table, Value};
+
+use crate::radar::try_parse_color;
+use crate::template::Template;
+
+/// Problem found in a config, located by its key path
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    /// Dotted key path, e.g. "colors.background" or "viewports.0.zoom"
+    pub path: String,
+
+    pub message: String,
+
+    /// 1-based line and column in the config file, when known
+    pub location: Option<(usize, usize)>,
+
+    /// 1-based column inside the value the problem is at, for templates
+    column_in_value: Option<usize>,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((line, column)) = self.location {
+            write!(f, "line {}, column {}: ", line, column)?;
+        }
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Every problem found in a config file
+#[derive(Debug, Clone, PartialEq, Error)]
+pub struct ConfigError {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.issues.len() {
+            1 => write!(f, "1 problem in config.toml")?,
+            n => write!(f, "{} problems in config.toml", n)?,
+        }
+        for issue in &self.issues {
+            write!(f, "\n  {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl ConfigError {
+    /// Error with a single issue at a dotted key path
+    pub fn new(path: &str, message: impl Into<String>) -> Self {
+        let issue = ConfigIssue {
+            path: path.to_string(),
+            message: message.into(),
+            location: None,
+            column_in_value: None,
+        };
+        Self { issues: vec![issue] }
+    }
+
+    /// Error for a file that is not valid TOML or does not match the config's shape
+    pub fn from_toml(error: &toml::de::Error, contents: &str) -> Self {
+        let issue = ConfigIssue {
+            path: String::new(),
+            message: error.message().to_string(),
+            location: error.span().map(|span| line_column(contents, span.start)),
+            column_in_value: None,
+        };
+        Self { issues: vec![issue] }
+    }
+
+    /// Fill in where each issue is in the file the config was read from
+    pub fn located(mut self, contents: &str) -> Self {
+        let Ok(document) = ImDocument::parse(contents) else {
+            return self;
+        };
+
+        for issue in &mut self.issues {
+            let (span, string) = find(document.as_item(), &issue.path);
+            let Some(span) = span else {
+                continue;
+            };
+
+            // Point inside string values, past the opening quote
+            let offset = match (string, issue.column_in_value) {
+                (true, Some(column)) => {
+                    let value = &contents[span.start..];
+                    let quote = value.chars().next().map_or(0, char::len_utf8);
+                    let inner: usize = value[quote..]
+                        .chars()
+                        .take(column - 1)
+                        .map(char::len_utf8)
+                        .sum();
+                    span.start + quote + inner
+                }
+                _ => span.start,
+            };
+            issue.location = Some(line_column(contents, offset));
+        }
+
+        self
+    }
+}
+
+/// Part of a parsed config file
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    Item(&'a Item),
+    Table(&'a Table),
+    Value(&'a Value),
+}
+
+impl<'a> Node<'a> {
+    fn span(self) -> Option<Range<usize>> {
+        match self {
+            Node::Item(item) => item.span(),
+            Node::Table(table) => table.span(),
+            Node::Value(value) => value.span(),
+        }
+    }
+
+    /// Child by key, or by index for arrays
+    fn child(self, part: &str) -> Option<Node<'a>> {
+        match self {
+            Node::Item(Item::Table(table)) | Node::Table(table) => table.get(part).map(Node::Item),
+            Node::Item(Item::ArrayOfTables(array)) => array.get(part.parse().ok()?).map(Node::Table),
+            Node::Item(Item::Value(value)) | Node::Value(value) => match value {
+                Value::InlineTable(table) => table.get(part).map(Node::Value),
+                Value::Array(array) => array.get(part.parse().ok()?).map(Node::Value),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn is_string(self) -> bool {
+        matches!(self, Node::Item(Item::Value(Value::String(_))) | Node::Value(Value::String(_)))
+    }
+}
+
+/// Span of the value at a dotted path, or of its closest parent in the file,
+/// and whether the path was found and holds a string
+fn find(root: &Item, path: &str) -> (Option<Range<usize>>, bool) {
+    let mut node = Node::Item(root);
+    let mut span = None;
+
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        match node.child(part) {
+            Some(child) => {
+                node = child;
+                span = node.span().or(span);
+            }
+            None => return (span, false),
+        }
+    }
+
+    (span, node.is_string())
+}
+
+/// 1-based line and column of a byte offset
+fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (line, column)
+}
+
+/// Collects problems while walking a config
+#[derive(Default)]
+pub struct Validator {
+    path: Vec<String>,
+    issues: Vec<ConfigIssue>,
+
+    /// Palette entries "$name" colors may refer to
+    palette: Vec<String>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check the fields of a nested table or array element
+    pub fn nested(&mut self, key: impl ToString, check: impl FnOnce(&mut Self)) {
+        self.path.push(key.to_string());
+        check(self);
+        self.path.pop();
+    }
+
+    fn report(&mut self, key: &str, message: String, column_in_value: Option<usize>) {
+        let path = self.path
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(key).filter(|key| !key.is_empty()))
+            .collect::<Vec<_>>()
+            .join(".");
+
+        self.issues.push(ConfigIssue { path, message, location: None, column_in_value });
+    }
+
+    pub fn error(&mut self, key: &str, message: impl Into<String>) {
+        self.report(key, message.into(), None);
+    }
+
+    /// Add the issues of an error, with their paths taken as relative to the current table
+    pub fn extend(&mut self, error: ConfigError) {
+        for issue in error.issues {
+            self.report(&issue.path, issue.message, issue.column_in_value);
+        }
+    }
+
+    /// Set the palette entries colors may refer to
+    pub fn set_palette<'a>(&mut self, names: impl IntoIterator<Item = &'a String>) {
+        self.palette = names.into_iter().cloned().collect();
+    }
+
+    /// Hex color such as "#0A0E1A" or "#0A0E1A80", a color name, or a palette reference
+    pub fn color(&mut self, key: &str, value: &str) {
+        match value.strip_prefix('$') {
+            Some(name) if !self.palette.iter().any(|entry| entry == name) => {
+                self.error(key, format!("no palette entry \"{}\"", name));
+            }
+            Some(_) => {}
+            None if try_parse_color(value).is_none() => {
+                self.error(
+                    key,
+                    format!("\"{}\" is not a color, expected hex like \"#0A0E1A\" or a name like \"amber\"", value)
+                );
+            }
+            None => {}
+        }
+    }
+
+    pub fn range<T: PartialOrd + fmt::Display>(&mut self, key: &str, value: T, range: RangeInclusive<T>) {
+        if !range.contains(&value) {
+            self.error(
+                key,
+                format!("{} is out of range, expected {} to {}", value, range.start(), range.end())
+            );
+        }
+    }
+
+    /// URL with one of the given schemes, e.g. ["ws", "wss"]
+    pub fn url(&mut self, key: &str, value: &str, schemes: &[&str]) {
+        match reqwest::Url::parse(value) {
+            Ok(url) if schemes.contains(&url.scheme()) => {}
+            Ok(url) => self.error(
+                key,
+                format!("\"{}\" scheme is not supported, expected {}", url.scheme(), schemes.join(" or "))
+            ),
+            Err(e) => self.error(key, format!("\"{}\" is not a valid URL: {}", value, e)),
+        }
+    }
+
+    /// Data tag template
+    pub fn template(&mut self, key: &str, source: &str) {
+        if let Err(e) = Template::parse(source) {
+            self.report(key, e.to_string(), Some(e.column()));
+        }
+    }
+
+    pub fn finish(self) -> Result<(), ConfigError> {
+        if self.issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError { issues: self.issues })
+        }
+    }
+}
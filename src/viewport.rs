@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::{DataTagConfig, RadarConfig};
+use crate::filters::FilterConfig;
+use crate::validation::Validator;
+
+/// How an extra viewport is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewportKind {
+    /// Picture-in-picture inset floating over the main scope
+    Inset,
+    /// Separate native window, e.g. for a second monitor
+    Window,
+}
+
+/// Extra scope viewport with its own camera and optional settings
+///
+/// All viewports share the same radar state; filters and data tag settings
+/// fall back to the main scope's when not set here.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ViewportConfig {
+    /// Title, also identifies the viewport
+    pub name: String,
+
+    /// Inset or separate window
+    #[serde(default = "default_kind")]
+    pub kind: ViewportKind,
+
+    /// Whether the viewport is shown
+    #[serde(default = "default_true")]
+    pub open: bool,
+
+    /// Camera center in studs (x, y)
+    #[serde(default)]
+    pub center: (f64, f64),
+
+    /// Zoom level (studs per pixel)
+    #[serde(default = "default_zoom")]
+    pub zoom: f64,
+
+    /// True heading shown at the top (0 = north up)
+    #[serde(default)]
+    pub rotation: f64,
+
+    /// Screen position of the inset or window, unset = placed automatically
+    #[serde(default)]
+    pub position: Option<(f32, f32)>,
+
+    /// Size in screen points
+    #[serde(default = "default_size")]
+    pub size: (f32, f32),
+
+    /// Display filters, unset = same as the main scope
+    #[serde(default)]
+    pub filters: Option<FilterConfig>,
+
+    /// Data tag settings, unset = same as the main scope
+    #[serde(default)]
+    pub data_tags: Option<DataTagConfig>,
+}
+
+fn default_true() -> bool { true }
+fn default_kind() -> ViewportKind { ViewportKind::Inset }
+fn default_zoom() -> f64 { 20.0 }
+fn default_size() -> (f32, f32) { (480.0, 360.0) }
+
+impl ViewportConfig {
+    pub fn new(name: String, kind: ViewportKind, center: (f64, f64), zoom: f64) -> Self {
+        Self {
+            name,
+            kind,
+            open: default_true(),
+            center,
+            zoom,
+            rotation: 0.0,
+            position: None,
+            size: default_size(),
+            filters: None,
+            data_tags: None,
+        }
+    }
+
+    pub fn validate(&self, v: &mut Validator) {
+        if self.name.trim().is_empty() {
+            v.error("name", "must not be empty");
+        }
+        v.range("zoom", self.zoom, 1.0..=1000.0);
+        if self.size.0 < 50.0 || self.size.1 < 50.0 {
+            v.error("size", "must be at least 50 x 50");
+        }
+        if let Some(filters) = &self.filters {
+            v.nested("filters", |v| filters.validate(v));
+        }
+        if let Some(data_tags) = &self.data_tags {
+            v.nested("data_tags", |v| data_tags.validate(v));
+        }
+    }
+
+    /// The main config with this viewport's overrides applied
+    pub fn apply(&self, base: &RadarConfig) -> RadarConfig {
+        let mut config = base.clone();
+        if let Some(filters) = &self.filters {
+            config.filters = filters.clone();
+        }
+        if let Some(data_tags) = &self.data_tags {
+            config.data_tags = data_tags.clone();
+        }
+        config
+    }
+
+    /// Whether the camera or window placement differs from another layout
+    pub fn layout_differs(&self, other: &ViewportConfig) -> bool {
+        self.open != other.open
+            || self.center != other.center
+            || self.zoom != other.zoom
+            || self.rotation != other.rotation
+            || self.position != other.position
+            || self.size != other.size
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::filters::FilterConfig;
+use crate::radar::Projection;
+use crate::types::TrackedAircraft;
+use crate::validation::Validator;
+
+/// Camera position of a scope
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct CameraView {
+    /// Center point in studs (x, y)
+    pub center: (f64, f64),
+
+    /// Zoom level (studs per pixel)
+    pub zoom: f64,
+
+    /// True heading shown at the top (0 = north up)
+    #[serde(default)]
+    pub rotation: f64,
+}
+
+impl CameraView {
+    pub fn from_projection(projection: &Projection) -> Self {
+        Self {
+            center: projection.center,
+            zoom: projection.studs_per_pixel,
+            rotation: projection.rotation,
+        }
+    }
+
+    pub fn apply_to(&self, projection: &mut Projection) {
+        projection.center = self.center;
+        projection.studs_per_pixel = self.zoom.clamp(1.0, 1000.0);
+        projection.set_rotation(self.rotation);
+    }
+}
+
+/// Named camera preset
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ViewPreset {
+    pub name: String,
+
+    #[serde(flatten)]
+    pub camera: CameraView,
+
+    /// Display filters applied on recall, unset = leave filters alone
+    #[serde(default)]
+    pub filters: Option<FilterConfig>,
+}
+
+/// Airport the scope can snap to
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AirportView {
+    /// ICAO code, e.g. "IRFD"
+    pub icao: String,
+
+    /// Airport reference point in studs (x, y)
+    pub position: (f64, f64),
+
+    /// Zoom level on snap, unset = views.airport_zoom
+    #[serde(default)]
+    pub zoom: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ViewsConfig {
+    /// Restore the last camera position on startup (kept in last_view.json)
+    #[serde(default = "default_true")]
+    pub restore_last: bool,
+
+    /// Saved views; the recall_view_1 to recall_view_9 keybindings recall the first nine
+    #[serde(default)]
+    pub presets: Vec<ViewPreset>,
+
+    /// Airports to snap to, in addition to those learned from traffic
+    #[serde(default)]
+    pub airports: Vec<AirportView>,
+
+    /// Zoom level used when snapping to an airport
+    #[serde(default = "default_airport_zoom")]
+    pub airport_zoom: f64,
+}
+
+fn default_true() -> bool { true }
+fn default_airport_zoom() -> f64 { 10.0 }
+
+impl Default for ViewsConfig {
+    fn default() -> Self {
+        Self {
+            restore_last: default_true(),
+            presets: Vec::new(),
+            airports: Vec::new(),
+            airport_zoom: default_airport_zoom(),
+        }
+    }
+}
+
+impl ViewsConfig {
+    pub fn validate(&self, v: &mut Validator) {
+        v.range("airport_zoom", self.airport_zoom, 1.0..=1000.0);
+
+        v.nested("presets", |v| {
+            for (i, preset) in self.presets.iter().enumerate() {
+                v.nested(i, |v| {
+                    v.range("zoom", preset.camera.zoom, 1.0..=1000.0);
+                    if let Some(filters) = &preset.filters {
+                        v.nested("filters", |v| filters.validate(v));
+                    }
+                });
+            }
+        });
+
+        v.nested("airports", |v| {
+            for (i, airport) in self.airports.iter().enumerate() {
+                if let Some(zoom) = airport.zoom {
+                    v.nested(i, |v| v.range("zoom", zoom, 1.0..=1000.0));
+                }
+            }
+        });
+    }
+
+    /// Find a preset by name (case-insensitive)
+    pub fn preset(&self, name: &str) -> Option<&ViewPreset> {
+        self.presets.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Camera for snapping to an airport, configured airports first, then learned ones
+    pub fn airport(&self, icao: &str, learned: &HashMap<String, AirportFix>) -> Option<CameraView> {
+        let configured = self.airports
+            .iter()
+            .find(|a| a.icao.eq_ignore_ascii_case(icao))
+            .map(|a| (a.position, a.zoom.unwrap_or(self.airport_zoom)));
+
+        configured
+            .or_else(|| {
+                learned
+                    .get(&icao.to_uppercase())
+                    .map(|fix| (fix.position, self.airport_zoom))
+            })
+            .map(|(center, zoom)| CameraView { center, zoom, rotation: 0.0 })
+    }
+}
+
+/// Airport position learned from aircraft on its ground
+#[derive(Debug, Clone, Copy)]
+pub struct AirportFix {
+    /// Mean position in studs (x, y)
+    pub position: (f64, f64),
+
+    /// Number of positions averaged (capped so the fix can follow corrections)
+    pub samples: u32,
+}
+
+const MAX_FIX_SAMPLES: u32 = 500;
+
+/// Refine airport positions from aircraft on the ground with a flight plan
+///
+/// Aircraft that have not flown yet are at their departure airport, and
+/// those that have are at their arrival airport.
+pub fn learn_airports(fixes: &mut HashMap<String, AirportFix>, aircraft: &HashMap<String, TrackedAircraft>) {
+    for tracked in aircraft.values() {
+        if tracked.info.is_on_ground != Some(true) {
+            continue;
+        }
+        let Some(plan) = &tracked.flight_plan else {
+            continue;
+        };
+        let airport = if tracked.has_been_airborne { &plan.arriving } else { &plan.departing };
+        let icao = airport.trim().to_uppercase();
+        if icao.is_empty() {
+            continue;
+        }
+
+        let position = (tracked.info.position.x, tracked.info.position.y);
+        let fix = fixes.entry(icao).or_insert(AirportFix { position, samples: 0 });
+        fix.samples = (fix.samples + 1).min(MAX_FIX_SAMPLES);
+
+        let weight = 1.0 / fix.samples as f64;
+        fix.position.0 += (position.0 - fix.position.0) * weight;
+        fix.position.1 += (position.1 - fix.position.1) * weight;
+    }
+}
+
+/// Get the last view file path
+pub fn last_view_path() -> PathBuf {
+    crate::config::data_path("last_view.json")
+}
+
+/// Load the main camera position from the last run, or none if it was never saved
+pub fn load_last_view() -> Result<Option<CameraView>> {
+    let path = last_view_path();
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .context("Failed to read last_view.json")?;
+    let camera = serde_json::from_str(&contents)
+        .context("Failed to parse last_view.json")?;
+
+    Ok(Some(camera))
+}
+
+/// Save the main camera position for the next run
+pub fn save_last_view(camera: &CameraView) -> Result<()> {
+    let contents = serde_json::to_string_pretty(camera)
+        .context("Failed to serialize last view")?;
+
+    std::fs::write(last_view_path(), contents)
+        .context("Failed to write last_view.json")?;
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::aircraft_db::WakeCategory;
+use crate::types::{FlightPhase, TrackedAircraft};
+use crate::validation::Validator;
+
+/// PTFS uses 1 knot = 0.5442765 studs/sec
+pub const STUDS_PER_NM: f64 = 0.5442765 * 3600.0;
+
+/// How the gap between two aircraft on final is measured and displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WakeMode {
+    /// Gap in nautical miles
+    Distance,
+    /// Gap in seconds at the follower's ground speed
+    Time,
+}
+
+/// Sequence on final approach with its wake separation check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WakeAdvisory {
+    /// Callsign of the aircraft ahead
+    pub leader: String,
+
+    /// Current gap in nautical miles
+    pub gap_nm: f64,
+
+    /// Current gap in seconds at the follower's ground speed
+    pub gap_secs: f64,
+
+    /// Required wake separation in nautical miles (0 if none applies)
+    pub required_nm: f64,
+
+    /// Gap is below the required wake separation
+    pub infringed: bool,
+}
+
+impl WakeAdvisory {
+    /// Gap formatted for a data tag, e.g. "4.2NM" or "85S"
+    pub fn gap_text(&self, mode: WakeMode) -> String {
+        match mode {
+            WakeMode::Distance => format!("{:.1}NM", self.gap_nm),
+            WakeMode::Time => format!("{:.0}S", self.gap_secs),
+        }
+    }
+}
+
+/// ICAO distance-based wake separation in nm for a leader/follower pair
+/// Returns 0 where only the radar minimum applies
+pub fn required_separation(leader: WakeCategory, follower: WakeCategory) -> f64 {
+    use WakeCategory::*;
+
+    match (leader, follower) {
+        (Super, Super) => 0.0,
+        (Super, Heavy) => 6.0,
+        (Super, Medium) => 7.0,
+        (Super, Light) => 8.0,
+        (Heavy, Heavy) => 4.0,
+        (Heavy, Medium) => 5.0,
+        (Heavy, Light) => 6.0,
+        (Medium, Light) => 5.0,
+        _ => 0.0,
+    }
+}
+
+/// Find aircraft in sequence on the same final and check their wake separation
+///
+/// A pair is in sequence when both are descending below `max_altitude` to the
+/// same filed arrival airport, flying within `max_track_diff` degrees of each
+/// other, and the leader is ahead of the follower within `lateral_tolerance_nm`
+/// of its track. Each follower is paired with the nearest such leader.
+/// Aircraft that dropped off the feed by `now` are left out, so their last
+/// position doesn't hold up the sequence.
+pub fn compute_advisories(
+    aircraft: &HashMap<String, TrackedAircraft>,
+    config: &WakeConfig,
+    now: i64,
+) -> HashMap<String, WakeAdvisory> {
+    let mut advisories = HashMap::new();
+
+    if !config.enabled {
+        return advisories;
+    }
+
+    // Without a filed arrival airport there is no telling which final an aircraft is on
+    let on_final: Vec<(&TrackedAircraft, &str)> = aircraft
+        .values()
+        .filter(|a| {
+            !a.is_off_feed(now)
+                && a.phase() == FlightPhase::Descending
+                && a.info.altitude <= config.max_altitude
+                && a.info.ground_speed > 0.0
+        })
+        .filter_map(|a| {
+            let arrival = a.flight_plan.as_ref()?.arriving.trim();
+            (!arrival.is_empty()).then_some((a, arrival))
+        })
+        .collect();
+
+    for (follower, follower_arrival) in &on_final {
+        let heading = (follower.info.heading - 90.0).to_radians();
+        let (dir_x, dir_y) = (heading.cos(), heading.sin());
+
+        let leader = on_final
+            .iter()
+            .filter(|(leader, arrival)| {
+                leader.callsign != follower.callsign && arrival.eq_ignore_ascii_case(follower_arrival)
+            })
+            .map(|(leader, _)| leader)
+            .filter(|leader| {
+                let track_diff = (leader.info.heading - follower.info.heading + 540.0) % 360.0 - 180.0;
+                track_diff.abs() <= config.max_track_diff
+            })
+            .filter_map(|leader| {
+                // Leader position in the follower's along/cross-track frame
+                let dx = leader.info.position.x - follower.info.position.x;
+                let dy = leader.info.position.y - follower.info.position.y;
+                let along = (dx * dir_x + dy * dir_y) / STUDS_PER_NM;
+                let cross = (dx * -dir_y + dy * dir_x) / STUDS_PER_NM;
+
+                (along > 0.0 && along <= config.max_gap_nm && cross.abs() <= config.lateral_tolerance_nm)
+                    .then_some((leader, along))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((leader, gap_nm)) = leader {
+            let required_nm = required_separation(leader.type_info.wake, follower.type_info.wake);
+            let gap_secs = gap_nm / follower.info.ground_speed * 3600.0;
+            let infringed = gap_nm < required_nm;
+
+            advisories.insert(
+                follower.callsign.clone(),
+                WakeAdvisory {
+                    leader: leader.callsign.clone(),
+                    gap_nm,
+                    gap_secs,
+                    required_nm,
+                    infringed,
+                },
+            );
+        }
+    }
+
+    advisories
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WakeConfig {
+    /// Enable wake separation advisories
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Show the gap as distance or time
+    #[serde(default = "default_mode")]
+    pub mode: WakeMode,
+
+    /// Only aircraft at or below this altitude (ft) are considered on final
+    #[serde(default = "default_max_altitude")]
+    pub max_altitude: f64,
+
+    /// Maximum track difference in degrees for two aircraft to be in sequence
+    #[serde(default = "default_max_track_diff")]
+    pub max_track_diff: f64,
+
+    /// Maximum cross-track offset of the leader in nm
+    #[serde(default = "default_lateral_tolerance")]
+    pub lateral_tolerance_nm: f64,
+
+    /// Pairs further apart than this (nm) are not in sequence
+    #[serde(default = "default_max_gap")]
+    pub max_gap_nm: f64,
+}
+
+fn default_true() -> bool { true }
+fn default_mode() -> WakeMode { WakeMode::Distance }
+fn default_max_altitude() -> f64 { 5000.0 }
+fn default_max_track_diff() -> f64 { 15.0 }
+fn default_lateral_tolerance() -> f64 { 0.5 }
+fn default_max_gap() -> f64 { 12.0 }
+
+impl WakeConfig {
+    pub fn validate(&self, v: &mut Validator) {
+        v.range("max_altitude", self.max_altitude, 0.0..=60000.0);
+        v.range("max_track_diff", self.max_track_diff, 0.0..=180.0);
+        v.range("lateral_tolerance_nm", self.lateral_tolerance_nm, 0.0..=10.0);
+        v.range("max_gap_nm", self.max_gap_nm, 0.0..=100.0);
+    }
+}
+
+impl Default for WakeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            mode: default_mode(),
+            max_altitude: default_max_altitude(),
+            max_track_diff: default_max_track_diff(),
+            lateral_tolerance_nm: default_lateral_tolerance(),
+            max_gap_nm: default_max_gap(),
+        }
+    }
+}